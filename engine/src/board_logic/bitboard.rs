@@ -0,0 +1,343 @@
+use crate::move_logic::{Dimensions, Square};
+use crate::piece_logic::{Color, Movement, PieceType, Reach};
+use std::sync::OnceLock;
+
+/// One bit per square, a1 being the lowest bit and the last square of the
+/// board the highest, rank by rank.
+pub type Bitboard = u128;
+
+const SQUARES: usize = 64;
+pub const ROOK_DIRECTIONS: [(isize, isize); 4] = [(0, 1), (0, -1), (1, 0), (-1, 0)];
+pub const BISHOP_DIRECTIONS: [(isize, isize); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+const KNIGHT_JUMPS: [(isize, isize); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const KING_STEPS: [(isize, isize); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+/// The diagonal steps forward a white and a black pawn capture with.
+const PAWN_CAPTURES: [[(isize, isize); 2]; 2] = [[(1, 1), (-1, 1)], [(1, -1), (-1, -1)]];
+
+pub fn index(dims: Dimensions, position: Square) -> usize {
+    position.rank() * dims.files() + position.file()
+}
+
+pub fn bit(dims: Dimensions, position: Square) -> Bitboard {
+    1 << index(dims, position)
+}
+
+/// The squares of the set bits, from a1 upwards.
+pub fn squares(dims: Dimensions, mut bits: Bitboard) -> impl Iterator<Item = Square> {
+    std::iter::from_fn(move || {
+        if bits == 0 {
+            return None;
+        }
+        let index = bits.trailing_zeros() as usize;
+        bits &= bits - 1;
+        Square::new(index % dims.files(), index / dims.files())
+    })
+}
+
+pub fn color_index(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+/// How many kinds of pieces have a bitboard of their own, all custom
+/// pieces sharing the last one.
+pub const PIECE_KINDS: usize = 7;
+pub const CUSTOM_INDEX: usize = PIECE_KINDS - 1;
+
+pub fn piece_index(piece_type: &PieceType) -> usize {
+    match piece_type {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+        PieceType::Custom(_) => CUSTOM_INDEX,
+    }
+}
+
+// The standard board looks its attacks up in precomputed tables, other
+// boards work them out square by square.
+
+pub fn knight_attacks(dims: Dimensions, position: Square) -> Bitboard {
+    if dims == Dimensions::STANDARD {
+        tables().knight[index(dims, position)]
+    } else {
+        leaps(dims, position, &KNIGHT_JUMPS)
+    }
+}
+
+pub fn king_attacks(dims: Dimensions, position: Square) -> Bitboard {
+    if dims == Dimensions::STANDARD {
+        tables().king[index(dims, position)]
+    } else {
+        leaps(dims, position, &KING_STEPS)
+    }
+}
+
+/// The squares a pawn of `color` standing on `position` attacks.
+pub fn pawn_attacks(dims: Dimensions, color: Color, position: Square) -> Bitboard {
+    if dims == Dimensions::STANDARD {
+        tables().pawn[color_index(color)][index(dims, position)]
+    } else {
+        leaps(dims, position, &PAWN_CAPTURES[color_index(color)])
+    }
+}
+
+pub fn rook_attacks(dims: Dimensions, position: Square, occupied: Bitboard) -> Bitboard {
+    if dims == Dimensions::STANDARD {
+        let tables = tables();
+        tables.rook[index(dims, position)].attacks(&tables.sliding, occupied)
+    } else {
+        slide(dims, position, occupied, &ROOK_DIRECTIONS)
+    }
+}
+
+pub fn bishop_attacks(dims: Dimensions, position: Square, occupied: Bitboard) -> Bitboard {
+    if dims == Dimensions::STANDARD {
+        let tables = tables();
+        tables.bishop[index(dims, position)].attacks(&tables.sliding, occupied)
+    } else {
+        slide(dims, position, occupied, &BISHOP_DIRECTIONS)
+    }
+}
+
+pub fn queen_attacks(dims: Dimensions, position: Square, occupied: Bitboard) -> Bitboard {
+    rook_attacks(dims, position, occupied) | bishop_attacks(dims, position, occupied)
+}
+
+/// The squares `movement` takes a piece of `color` on `position` to when
+/// the squares in `occupied` hold pieces, whether it may move or capture
+/// there or not.
+pub fn movement_targets(
+    dims: Dimensions,
+    movement: &Movement,
+    color: Color,
+    position: Square,
+    occupied: Bitboard,
+) -> Bitboard {
+    let mut targets = 0;
+    for &(x, y) in movement.directions() {
+        let direction = if color == Color::White {
+            (x, y)
+        } else {
+            (x, -y)
+        };
+        targets |= match movement.reach() {
+            Reach::Leap => leaps(dims, position, &[direction]),
+            Reach::Ride => slide(dims, position, occupied, &[direction]),
+            Reach::Hop => squares(
+                dims,
+                slide(dims, position, occupied, &[direction]) & occupied,
+            )
+            .next()
+            .and_then(|hurdle| dims.offset(hurdle, direction))
+            .map_or(0, |landing| bit(dims, landing)),
+        };
+    }
+    targets
+}
+
+/// The squares one of `jumps` away from `position`.
+fn leaps(dims: Dimensions, position: Square, jumps: &[(isize, isize)]) -> Bitboard {
+    jumps
+        .iter()
+        .filter_map(|jump| dims.offset(position, *jump))
+        .fold(0, |bits, target| bits | bit(dims, target))
+}
+
+struct AttackTables {
+    knight: Vec<Bitboard>,
+    king: Vec<Bitboard>,
+    pawn: [Vec<Bitboard>; 2],
+    rook: Vec<Magic>,
+    bishop: Vec<Magic>,
+    /// Sliding attacks for every square and relevant occupancy, indexed
+    /// through the magics.
+    sliding: Vec<Bitboard>,
+}
+
+fn tables() -> &'static AttackTables {
+    static TABLES: OnceLock<AttackTables> = OnceLock::new();
+    TABLES.get_or_init(AttackTables::new)
+}
+
+impl AttackTables {
+    fn new() -> AttackTables {
+        let dims = Dimensions::STANDARD;
+        let table = |jumps: &[(isize, isize)]| -> Vec<Bitboard> {
+            dims.squares()
+                .map(|position| leaps(dims, position, jumps))
+                .collect()
+        };
+        let mut sliding = Vec::new();
+        let rook = dims
+            .squares()
+            .map(|position| {
+                let magic = ROOK_MAGICS[index(dims, position)];
+                Magic::new(position, &ROOK_DIRECTIONS, magic, &mut sliding)
+            })
+            .collect();
+        let bishop = dims
+            .squares()
+            .map(|position| {
+                let magic = BISHOP_MAGICS[index(dims, position)];
+                Magic::new(position, &BISHOP_DIRECTIONS, magic, &mut sliding)
+            })
+            .collect();
+        AttackTables {
+            knight: table(&KNIGHT_JUMPS),
+            king: table(&KING_STEPS),
+            pawn: [table(&PAWN_CAPTURES[0]), table(&PAWN_CAPTURES[1])],
+            rook,
+            bishop,
+            sliding,
+        }
+    }
+}
+
+/// Maps the pieces standing in a slider's way onto its slot in the attack
+/// table with a single multiplication. Only used on the standard board,
+/// whose squares all fit in the lower 64 bits.
+struct Magic {
+    mask: Bitboard,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    /// Appends the attacks of the slider on `position` for every blocker
+    /// arrangement to `table`.
+    fn new(
+        position: Square,
+        directions: &[(isize, isize)],
+        magic: u64,
+        table: &mut Vec<Bitboard>,
+    ) -> Magic {
+        let dims = Dimensions::STANDARD;
+        // squares on the edge of a ray never block anything behind them
+        let mask = directions.iter().fold(0, |mask, direction| {
+            let mut current = position;
+            let mut ray = 0;
+            while let Some(next) = dims.offset(current, *direction) {
+                if dims.offset(next, *direction).is_some() {
+                    ray |= bit(dims, next);
+                }
+                current = next;
+            }
+            mask | ray
+        });
+        let bits = mask.count_ones();
+        let magic = Magic {
+            mask,
+            magic,
+            shift: (SQUARES as u32) - bits,
+            offset: table.len(),
+        };
+        table.resize(magic.offset + (1 << bits), 0);
+        let mut blockers: Bitboard = 0;
+        loop {
+            let attacks = slide(dims, position, blockers, directions);
+            let slot = magic.offset + magic.slot(blockers);
+            debug_assert!(table[slot] == 0 || table[slot] == attacks);
+            table[slot] = attacks;
+            blockers = blockers.wrapping_sub(mask) & mask;
+            if blockers == 0 {
+                break;
+            }
+        }
+        magic
+    }
+
+    fn attacks(&self, table: &[Bitboard], occupied: Bitboard) -> Bitboard {
+        table[self.offset + self.slot(occupied)]
+    }
+
+    fn slot(&self, occupied: Bitboard) -> usize {
+        (((occupied & self.mask) as u64).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+/// Walks each direction from `position` until the edge or the first
+/// occupied square, which is included.
+pub fn slide(
+    dims: Dimensions,
+    position: Square,
+    occupied: Bitboard,
+    directions: &[(isize, isize)],
+) -> Bitboard {
+    let mut attacks = 0;
+    for direction in directions {
+        let mut current = position;
+        while let Some(next) = dims.offset(current, *direction) {
+            attacks |= bit(dims, next);
+            if occupied & bit(dims, next) != 0 {
+                break;
+            }
+            current = next;
+        }
+    }
+    attacks
+}
+
+// Found by a seeded random search for multipliers without harmful collisions.
+#[rustfmt::skip]
+const ROOK_MAGICS: [u64; 64] = [
+    0x1080_0040_0880_1020, 0x0840_0920_02C0_3000, 0x1900_2000_1040_0900, 0x0880_1000_0800_0480,
+    0x4200_1004_2008_0200, 0x8100_0201_0008_0400, 0x0200_0401_1088_6200, 0x0200_0080_4022_0411,
+    0x0404_8000_8440_0220, 0x0000_4010_0040_2000, 0x0086_0010_8122_0440, 0x0408_8008_0010_0280,
+    0x000A_0012_0104_0820, 0x8848_8002_0084_0080, 0x4001_0001_0004_0200, 0x0442_0001_0210_5084,
+    0x9080_0100_2080_4100, 0x0040_4040_0020_1009, 0x0000_8080_1000_2009, 0x2200_0900_21D0_0100,
+    0x0008_0080_0804_0080, 0x0004_0040_0201_0040, 0x0011_0400_0801_5042, 0x0000_0A00_0176_8104,
+    0x0000_8000_8020_4009, 0x2010_0041_4000_2001, 0x9800_2002_8010_0080, 0x1000_1000_8008_0080,
+    0x0442_000A_0004_9020, 0x2100_0400_8002_0080, 0x0800_1204_0090_0148, 0x0010_040A_0012_8541,
+    0x2800_8040_0080_0030, 0x1010_0020_0040_0041, 0x4000_2000_1100_4100, 0x0610_0084_1080_0800,
+    0x0400_8024_0280_0800, 0xC100_0200_8080_0400, 0x0002_0008_0200_0401, 0x0182_0858_8200_0401,
+    0x0220_2040_0080_8000, 0x2860_1000_4002_4022, 0x0001_0020_0411_0040, 0x9910_1042_000A_0020,
+    0x0004_0800_0400_8080, 0x0010_0400_0200_8080, 0x2012_0048_8102_0004, 0x8300_8424_4482_0011,
+    0x0088_4038_8201_0200, 0x0820_4000_8021_0100, 0x0110_9100_40A0_0300, 0x0801_1002_8008_0480,
+    0x0242_0090_0820_0600, 0x1002_0004_8950_0200, 0x0040_8002_0001_0080, 0x0091_8000_4100_0080,
+    0x0000_2093_0048_8001, 0x04C1_0024_1482_4001, 0x0200_2000_0B00_1041, 0x7000_1000_0420_0901,
+    0x8002_0020_0410_0802, 0x3001_0002_084C_0007, 0x0888_2218_0081_3004, 0x4000_0028_4084_0112,
+];
+
+#[rustfmt::skip]
+const BISHOP_MAGICS: [u64; 64] = [
+    0xA010_0411_0800_3100, 0x0060_8202_0A00_2900, 0x6810_0106_1920_0000, 0x0828_1A05_2000_0408,
+    0x0001_1040_0100_0400, 0x0018_9010_0804_8400, 0x0004_0A02_1024_5280, 0x0002_0021_0808_A402,
+    0x9140_0484_1082_1200, 0x0800_0910_1082_0041, 0x2050_4804_8322_02C0, 0x0100_0914_0108_1000,
+    0x8021_0111_4000_0012, 0x0810_0208_0445_0400, 0x208B_0542_1090_08A2, 0x0080_084A_0804_0204,
+    0x0040_E2A8_0811_244C, 0x2505_0220_0800_8108, 0x0430_2201_0042_0040, 0x010A_0404_2022_0040,
+    0x1105_0002_9040_0000, 0x0093_0012_0082_2120, 0x4000_A620_4804_3004, 0x2801_2004_8A01_5004,
+    0x0060_9000_2A02_0814, 0x4404_2000_2408_00D0, 0x0110_2800_040A_4400, 0x1004_0800_8022_0040,
+    0x0001_0010_1100_4024, 0x0010_0440_0080_5040, 0x0914_0412_0082_0100, 0x0004_8210_1282_1480,
+    0x0024_0405_00C0_5021, 0x0088_6110_0208_0200, 0x0116_080A_0004_0020, 0x4000_0200_8008_0080,
+    0x2450_4501_4084_0040, 0x0000_8802_0148_4100, 0x0222_0204_0402_0092, 0x8081_1106_0000_2E00,
+    0x2842_1011_0500_0801, 0x1100_8090_0800_1025, 0x0002_0202_221C_0400, 0x0422_0140_2200_9020,
+    0x0210_0461_0210_0C00, 0xC004_0080_8202_9102, 0x00AA_4618_0110_1200, 0x0404_0800_8020_1108,
+    0x0205_4210_8C20_5002, 0x0410_5448_0410_0100, 0x0040_9108_4110_0000, 0x0400_2000_4202_1100,
+    0x0000_4204_8504_00C0, 0x0200_1004_10A4_2102, 0x1040_0208_0121_0102, 0x0805_0404_1042_0000,
+    0x2884_8041_3010_0200, 0x800C_2622_0124_2000, 0x1058_0001_9410_8800, 0x0014_2210_5442_0204,
+    0x0104_0000_12A0_2200, 0x0200_8810_0330_0100, 0x0140_4002_0284_0100, 0x0402_0208_0101_0201,
+];
@@ -0,0 +1,208 @@
+use super::*;
+
+/// Sets up a position piece by piece, e.g. for a board editor, checking that
+/// it could occur in a game before handing out the board.
+#[derive(Clone, Debug)]
+pub struct PositionBuilder {
+    board: ChessBoard,
+    to_move: Color,
+    passant_pos: Option<Square>,
+    /// A square off the board a piece was placed on or removed from, which
+    /// `validate` reports.
+    off_board: Option<Square>,
+}
+
+impl PositionBuilder {
+    /// Starts from an empty standard board with White to move.
+    pub fn new() -> PositionBuilder {
+        PositionBuilder::with_dimensions(Dimensions::STANDARD)
+    }
+
+    /// Starts from an empty board of any size with White to move.
+    pub fn with_dimensions(dims: Dimensions) -> PositionBuilder {
+        PositionBuilder {
+            board: init_sized_board(dims),
+            to_move: Color::White,
+            passant_pos: None,
+            off_board: None,
+        }
+    }
+
+    /// Starts from an existing position to edit it.
+    pub fn from_board(board: &ChessBoard, to_move: Color) -> PositionBuilder {
+        PositionBuilder {
+            board: board.clone(),
+            to_move,
+            passant_pos: board.en_passant_square(),
+            off_board: None,
+        }
+    }
+
+    /// Puts `piece` on `position`, replacing whatever stood there.
+    pub fn place(&mut self, mut piece: Piece, position: Square) -> &mut PositionBuilder {
+        if !self.board.dims.contains(position) {
+            self.off_board = Some(position);
+            return self;
+        }
+        if piece.piece_type == PieceType::Pawn
+            && position.rank() != self.board.pawn_rank(piece.color)
+        {
+            piece.moved();
+        }
+        self.board.set_space(position, Some(piece));
+        self
+    }
+
+    pub fn remove(&mut self, position: Square) -> &mut PositionBuilder {
+        if !self.board.dims.contains(position) {
+            self.off_board = Some(position);
+            return self;
+        }
+        self.board.set_space(position, None);
+        self
+    }
+
+    /// Empties the board, dropping castling rights and the en passant square.
+    pub fn clear(&mut self) -> &mut PositionBuilder {
+        self.board = init_sized_board(self.board.dims);
+        self.passant_pos = None;
+        self.off_board = None;
+        self
+    }
+
+    pub fn side_to_move(&mut self, color: Color) -> &mut PositionBuilder {
+        self.to_move = color;
+        self
+    }
+
+    pub fn castling_rights(&mut self, castling_rights: CastlingRights) -> &mut PositionBuilder {
+        self.board.castling_rights = castling_rights;
+        self
+    }
+
+    /// The square a pawn just skipped with its double step, if any.
+    pub fn en_passant(&mut self, passant_pos: Option<Square>) -> &mut PositionBuilder {
+        self.passant_pos = passant_pos;
+        self
+    }
+
+    pub fn board(&self) -> &ChessBoard {
+        &self.board
+    }
+
+    /// Checks that no square off the board was used, each side has one king,
+    /// no pawn stands on a back rank, the side that just moved is not in
+    /// check and the castling rights and en passant square fit the pieces.
+    /// Castling rights refer to the outermost rook on each side, as "KQkq"
+    /// does in FEN.
+    pub fn validate(&self) -> Result<(), ChessError> {
+        let invalid = |reason: String| Err(ChessError::InvalidPosition(reason));
+        let board = &self.board;
+        if let Some(position) = self.off_board {
+            return invalid(format!("{} is off the {} board", position, board.dims));
+        }
+        for &color in &[Color::White, Color::Black] {
+            let kings = board.bitboards[color_index(color)][piece_index(&PieceType::King)];
+            match kings.count_ones() {
+                0 => return invalid(format!("{:?} has no king", color)),
+                1 => (),
+                count => return invalid(format!("{:?} has {} kings", color, count)),
+            }
+        }
+        let pawns = board.bitboards[0][piece_index(&PieceType::Pawn)]
+            | board.bitboards[1][piece_index(&PieceType::Pawn)];
+        if let Some(position) = squares(board.dims, pawns)
+            .find(|position| position.rank() == 0 || position.rank() == board.dims.ranks() - 1)
+        {
+            return invalid(format!("pawn on the back rank at {}", position));
+        }
+        if board.is_checked(self.to_move.opposite()) {
+            return invalid(format!(
+                "{:?} is in check with {:?} to move",
+                self.to_move.opposite(),
+                self.to_move
+            ));
+        }
+        for &color in &[Color::White, Color::Black] {
+            for &(side, name) in &[
+                (SpecialMove::CastlingRight, "king"),
+                (SpecialMove::CastlingLeft, "queen"),
+            ] {
+                if board.castling_rights.allows(color, side)
+                    && board.outermost_rook(color, side).is_none()
+                {
+                    return invalid(format!(
+                        "{:?} cannot castle {} side without its king on rank {} and a rook on that side of it",
+                        color,
+                        name,
+                        board.home_rank(color) + 1
+                    ));
+                }
+            }
+        }
+        self.passant_connection().map(|_| ())
+    }
+
+    /// The validated position and the side to move.
+    pub fn build(&self) -> Result<(ChessBoard, Color), ChessError> {
+        self.validate()?;
+        let mut board = self.board.clone();
+        board.passant_connection = self.passant_connection()?;
+        for &color in &[Color::White, Color::Black] {
+            for (side_index, &side) in [SpecialMove::CastlingLeft, SpecialMove::CastlingRight]
+                .iter()
+                .enumerate()
+            {
+                if let Some(rook_file) = board.outermost_rook(color, side) {
+                    board.castling_rooks[color_index(color)][side_index] = rook_file;
+                }
+            }
+        }
+        board.chess960 = board.has_chess960_castling();
+        board.zobrist = board.full_zobrist_key(self.to_move);
+        Ok((board, self.to_move))
+    }
+
+    /// Pairs the en passant square with the pawn that can be captured there.
+    fn passant_connection(&self) -> Result<Option<(Square, Square)>, ChessError> {
+        let board = &self.board;
+        let passant_pos = match self.passant_pos {
+            Some(passant_pos) => passant_pos,
+            None => return Ok(None),
+        };
+        let (passant_rank, forward) = if self.to_move == Color::White {
+            (board.dims.ranks() - 3, -1)
+        } else {
+            (2, 1)
+        };
+        let pawn_pos = board
+            .dims
+            .offset(passant_pos, (0, forward))
+            .filter(|pawn_pos| {
+                matches!(
+                    board.ref_piece(*pawn_pos),
+                    Some(pawn) if pawn.piece_type == PieceType::Pawn && pawn.color != self.to_move
+                )
+            });
+        let start_pos = board.dims.offset(passant_pos, (0, -forward));
+        match pawn_pos {
+            Some(pawn_pos)
+                if passant_pos.rank() == passant_rank
+                    && board.ref_piece(passant_pos).is_none()
+                    && start_pos.is_some_and(|start_pos| board.ref_piece(start_pos).is_none()) =>
+            {
+                Ok(Some((passant_pos, pawn_pos)))
+            }
+            _ => Err(ChessError::InvalidPosition(format!(
+                "no pawn can be captured en passant on {}",
+                passant_pos
+            ))),
+        }
+    }
+}
+
+impl Default for PositionBuilder {
+    fn default() -> PositionBuilder {
+        PositionBuilder::new()
+    }
+}
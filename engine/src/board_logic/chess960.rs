@@ -0,0 +1,154 @@
+use super::*;
+
+/// Where the knights go among the five squares left once the bishops and
+/// queen are placed.
+const KNIGHT_PLACEMENTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+/// The back rank of Chess960 start position `index`, from the a-file on.
+/// Positions are numbered 0 to 959 as Scharnagl does, 518 being the
+/// standard setup.
+pub fn chess960_back_rank(index: usize) -> Option<[PieceType; BOARD_SIZE]> {
+    if index >= 960 {
+        return None;
+    }
+    let mut back_rank: [Option<PieceType>; BOARD_SIZE] = Default::default();
+    let mut rest = index;
+    // one bishop on a light square, one on a dark square
+    back_rank[rest % 4 * 2 + 1] = Some(PieceType::Bishop);
+    rest /= 4;
+    back_rank[rest % 4 * 2] = Some(PieceType::Bishop);
+    rest /= 4;
+    let mut place = |nth_empty: usize, piece_type: PieceType| {
+        let file = (0..BOARD_SIZE)
+            .filter(|file| back_rank[*file].is_none())
+            .nth(nth_empty)
+            .unwrap();
+        back_rank[file] = Some(piece_type);
+    };
+    place(rest % 6, PieceType::Queen);
+    rest /= 6;
+    let (first, second) = KNIGHT_PLACEMENTS[rest];
+    place(second, PieceType::Knight);
+    place(first, PieceType::Knight);
+    // the king always ends up between the rooks
+    place(0, PieceType::Rook);
+    place(0, PieceType::King);
+    place(0, PieceType::Rook);
+    Some(back_rank.map(|piece_type| piece_type.unwrap()))
+}
+
+impl ChessBoard {
+    /// Sets up `color` for Chess960 start position `index`, see
+    /// `chess960_back_rank`, with castling moves given as the king taking
+    /// its own rook. Needs a standard board.
+    pub fn chess960_pieces(&mut self, color: Color, index: usize) -> Result<(), ChessError> {
+        if self.dims != Dimensions::STANDARD {
+            return Err(ChessError::InvalidPosition(format!(
+                "Chess960 is played on an 8x8 board, not {}",
+                self.dims
+            )));
+        }
+        let back_rank = chess960_back_rank(index).ok_or_else(|| {
+            ChessError::InvalidPosition(format!("there is no Chess960 start position {}", index))
+        })?;
+        let rank = self.home_rank(color);
+        let pawn_rank = self.pawn_rank(color);
+        for file in 0..BOARD_SIZE {
+            self.add_piece(piece_make(color, PieceType::Pawn), square(file, pawn_rank));
+        }
+        for (file, piece_type) in back_rank.iter().enumerate() {
+            self.add_piece(piece_make(color, piece_type.clone()), square(file, rank));
+        }
+        let mut rooks = (0..BOARD_SIZE).filter(|file| back_rank[*file] == PieceType::Rook);
+        self.castling_rooks[color_index(color)] = [rooks.next().unwrap(), rooks.next().unwrap()];
+        let mut castling_rights = self.castling_rights;
+        castling_rights.grant(color, SpecialMove::CastlingRight);
+        castling_rights.grant(color, SpecialMove::CastlingLeft);
+        self.set_castling_rights(castling_rights);
+        self.chess960 = true;
+        Ok(())
+    }
+
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+
+    /// Where the king lands and where the rook moves from and to when
+    /// `color` castles: the king goes to the c-file or the second file from
+    /// the right, the g-file on a standard board, and the rook next to it,
+    /// wherever they started.
+    pub(super) fn castling_targets(
+        &self,
+        color: Color,
+        side: SpecialMove,
+    ) -> (Square, (Square, Square)) {
+        let rank = self.home_rank(color);
+        let [queen_rook, king_rook] = self.castling_rooks[color_index(color)];
+        if side == SpecialMove::CastlingRight {
+            let king_file = self.dims.files() - 2;
+            (
+                square(king_file, rank),
+                (square(king_rook, rank), square(king_file - 1, rank)),
+            )
+        } else {
+            (square(2, rank), (square(queen_rook, rank), square(3, rank)))
+        }
+    }
+
+    /// The file of the rook of `color` furthest from its king on `side`, the
+    /// one "K" and "Q" in a FEN position refer to.
+    pub(super) fn outermost_rook(&self, color: Color, side: SpecialMove) -> Option<usize> {
+        let rank = self.home_rank(color);
+        let king_file = self
+            .king_position(color)
+            .filter(|king_pos| king_pos.rank() == rank)?
+            .file();
+        let is_rook = |file: &usize| {
+            matches!(
+                self.ref_piece(square(*file, rank)),
+                Some(rook) if rook.piece_type == PieceType::Rook && rook.color == color
+            )
+        };
+        if side == SpecialMove::CastlingRight {
+            (king_file + 1..self.dims.files()).rev().find(is_rook)
+        } else {
+            (0..king_file).find(is_rook)
+        }
+    }
+
+    /// Castling with a king off its middle file, the e-file on a standard
+    /// board, or a rook off the corners only happens in Chess960.
+    pub(super) fn has_chess960_castling(&self) -> bool {
+        [Color::White, Color::Black].iter().any(|&color| {
+            let rank = self.home_rank(color);
+            let king_home = self.king_position(color) == Some(square(self.dims.files() / 2, rank));
+            [SpecialMove::CastlingLeft, SpecialMove::CastlingRight]
+                .iter()
+                .filter(|side| self.castling_rights.allows(color, **side))
+                .any(|side| {
+                    let (_, (rook_pos, _)) = self.castling_targets(color, *side);
+                    !king_home || rook_pos.file() != self.default_rook_file(*side)
+                })
+        })
+    }
+
+    /// The corner a rook castling to `side` starts on in standard chess.
+    pub(super) fn default_rook_file(&self, side: SpecialMove) -> usize {
+        if side == SpecialMove::CastlingRight {
+            self.dims.files() - 1
+        } else {
+            0
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use super::*;
+
+/// Reads a position in Forsyth-Edwards Notation, returning the board along
+/// with the side to move, the halfmove clock and the fullmove number. The
+/// two move counters may be left out and then default to "0 1". The size of
+/// the board follows from the ranks given, e.g. "rnqknr/pppppp/6/6/PPPPPP/RNQKNR"
+/// for Los Alamos chess, with empty runs of more than 9 squares written as
+/// one number.
+pub fn parse_fen(fen: &str) -> Result<(ChessBoard, Color, usize, usize), ChessError> {
+    parse_fen_with_pieces(fen, &[])
+}
+
+/// Reads a FEN position which may hold the custom pieces in `pieces` by
+/// their letters, see `parse_fen`.
+pub fn parse_fen_with_pieces(
+    fen: &str,
+    pieces: &[PieceDefinition],
+) -> Result<(ChessBoard, Color, usize, usize), ChessError> {
+    let bad_fen = |reason: String| ChessError::BadFen(reason);
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    if fields.len() != 6 && fields.len() != 4 {
+        return Err(bad_fen(format!(
+            "expected 6 fields, found {}",
+            fields.len()
+        )));
+    }
+
+    let ranks: Vec<&str> = fields[0].split('/').collect();
+    if !(4..=MAX_BOARD_SIZE).contains(&ranks.len()) {
+        return Err(bad_fen(format!(
+            "expected 4 to {} ranks, found {}",
+            MAX_BOARD_SIZE,
+            ranks.len()
+        )));
+    }
+    // a board only to look the letters up, as the size is not known yet
+    let mut piece_types = init_board();
+    for definition in pieces {
+        piece_types.define_piece(definition.clone())?;
+    }
+    let mut placements = Vec::new();
+    let mut width = None;
+    for (row, pieces) in ranks.iter().enumerate() {
+        let rank = ranks.len() - 1 - row;
+        let mut file = 0;
+        let mut empty = 0;
+        for letter in pieces.chars() {
+            if let Some(digit) = letter.to_digit(10) {
+                if empty == 0 && digit == 0 {
+                    return Err(bad_fen(format!("empty run of 0 on rank {}", rank + 1)));
+                }
+                empty = empty * 10 + digit as usize;
+                // stop long runs before they can overflow
+                if file + empty > MAX_BOARD_SIZE {
+                    return Err(bad_fen(format!("rank {} has too many files", rank + 1)));
+                }
+                continue;
+            }
+            file += std::mem::take(&mut empty);
+            let piece_type = piece_types.piece_type_for(letter).ok_or_else(|| {
+                bad_fen(format!("unknown piece '{}' on rank {}", letter, rank + 1))
+            })?;
+            let color = if letter.is_ascii_uppercase() {
+                Color::White
+            } else {
+                Color::Black
+            };
+            let position = Square::new(file, rank)
+                .ok_or_else(|| bad_fen(format!("rank {} has too many files", rank + 1)))?;
+            placements.push((position, color, piece_type));
+            file += 1;
+        }
+        file += empty;
+        if file > MAX_BOARD_SIZE {
+            return Err(bad_fen(format!("rank {} has too many files", rank + 1)));
+        }
+        match width {
+            Some(width) if width != file => {
+                return Err(bad_fen(format!(
+                    "rank {} has {} files instead of {}",
+                    rank + 1,
+                    file,
+                    width
+                )))
+            }
+            _ => width = Some(file),
+        }
+    }
+    let files = width.unwrap_or(0);
+    let dims = Dimensions::new(files, ranks.len()).map_err(|_| {
+        bad_fen(format!(
+            "cannot play on a board of {}x{} squares",
+            files,
+            ranks.len()
+        ))
+    })?;
+
+    let mut board = init_sized_board(dims);
+    board.custom_pieces = piece_types.custom_pieces;
+    for (position, color, piece_type) in placements {
+        if piece_type == PieceType::King && board.king_position(color).is_some() {
+            return Err(bad_fen(format!("more than one {:?} king", color)));
+        }
+        let mut piece = piece_make(color, piece_type);
+        if piece.piece_type == PieceType::Pawn && position.rank() != board.pawn_rank(color) {
+            piece.moved();
+        }
+        board.add_piece(piece, position);
+    }
+
+    let to_move = match fields[1] {
+        "w" => Color::White,
+        "b" => Color::Black,
+        other => return Err(bad_fen(format!("unknown side to move '{}'", other))),
+    };
+
+    board.castling_rights = parse_castling(&mut board, fields[2])
+        .ok_or_else(|| bad_fen(format!("invalid castling rights '{}'", fields[2])))?;
+    board.chess960 = board.has_chess960_castling();
+
+    if fields[3] != "-" {
+        let passant_pos: Square = fields[3]
+            .parse()
+            .map_err(|_| bad_fen(format!("invalid en passant square '{}'", fields[3])))?;
+        let (passant_rank, forward) = if to_move == Color::White {
+            (board.dims.ranks() - 3, -1)
+        } else {
+            (2, 1)
+        };
+        let pawn_pos = board
+            .dims
+            .offset(passant_pos, (0, forward))
+            .filter(|pawn_pos| {
+                matches!(
+                    board.ref_piece(*pawn_pos),
+                    Some(pawn) if pawn.piece_type == PieceType::Pawn && pawn.color != to_move
+                )
+            });
+        match pawn_pos {
+            Some(pawn_pos)
+                if passant_pos.rank() == passant_rank && board.ref_piece(passant_pos).is_none() =>
+            {
+                board.passant_connection = Some((passant_pos, pawn_pos))
+            }
+            _ => {
+                return Err(bad_fen(format!(
+                    "no pawn can be captured en passant on {}",
+                    passant_pos
+                )))
+            }
+        }
+    }
+
+    let (halfmove_clock, fullmove_number): (usize, usize) = match fields.get(4..6) {
+        Some(counters) => (
+            counters[0]
+                .parse()
+                .map_err(|_| bad_fen(format!("invalid halfmove clock '{}'", counters[0])))?,
+            counters[1]
+                .parse()
+                .ok()
+                .filter(|fullmove_number| *fullmove_number > 0)
+                .ok_or_else(|| bad_fen(format!("invalid fullmove number '{}'", counters[1])))?,
+        ),
+        None => (0, 1),
+    };
+    // The game counts plies, twice the fullmove number.
+    if fullmove_number.checked_mul(2).is_none() {
+        return Err(bad_fen("fullmove number too large".to_string()));
+    }
+    board.zobrist = board.full_zobrist_key(to_move);
+    Ok((board, to_move, halfmove_clock, fullmove_number))
+}
+
+/// Takes the check counters of Three-check and other variants won by giving
+/// `limit` checks out of a FEN position, returning the plain position and
+/// the checks given by White and Black. The counters may be the checks each
+/// side has left to give, after the en passant square, e.g. "3+2", or the
+/// checks given, at the end, e.g. "+0+1". Without them no checks were given.
+pub fn split_check_counters(fen: &str, limit: usize) -> Result<(String, [usize; 2]), ChessError> {
+    let mut fields: Vec<&str> = fen.split_whitespace().collect();
+    let (index, given) = match fields.iter().position(|field| field.contains('+')) {
+        Some(index) => (index, fields[index].starts_with('+')),
+        None => return Ok((fen.to_string(), [0, 0])),
+    };
+    let invalid = || ChessError::BadFen(format!("invalid check counters '{}'", fields[index]));
+    let counters: Vec<usize> = fields[index]
+        .trim_start_matches('+')
+        .split('+')
+        .map(|counter| counter.parse().ok().filter(|checks| *checks <= limit))
+        .collect::<Option<_>>()
+        .ok_or_else(invalid)?;
+    let checks = match (counters.as_slice(), given) {
+        (&[white, black], true) if index == fields.len() - 1 => [white, black],
+        (&[white, black], false) if index == 4 => [limit - white, limit - black],
+        _ => return Err(invalid()),
+    };
+    fields.remove(index);
+    Ok((fields.join(" "), checks))
+}
+
+/// Adds the checks each side has left to give to a FEN position, e.g.
+/// "3+2" after the en passant square, see `split_check_counters`.
+pub fn join_check_counters(fen: &str, checks: [usize; 2], limit: usize) -> String {
+    let mut fields: Vec<String> = fen.split_whitespace().map(str::to_string).collect();
+    let counters = format!(
+        "{}+{}",
+        limit.saturating_sub(checks[0]),
+        limit.saturating_sub(checks[1])
+    );
+    fields.insert(4.min(fields.len()), counters);
+    fields.join(" ")
+}
+
+/// Reads "KQkq" style castling rights, where a letter means the outermost
+/// rook on that side, as well as the rook files of X-FEN and Shredder-FEN,
+/// e.g. "HFhf", remembering which rooks castle.
+fn parse_castling(board: &mut ChessBoard, field: &str) -> Option<CastlingRights> {
+    let mut rights = CastlingRights::default();
+    if field == "-" {
+        return Some(rights);
+    }
+    if field.is_empty() {
+        return None;
+    }
+    for letter in field.chars() {
+        let color = if letter.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+        let (side, rook_file) = match letter.to_ascii_uppercase() {
+            'K' => (
+                SpecialMove::CastlingRight,
+                board
+                    .outermost_rook(color, SpecialMove::CastlingRight)
+                    .unwrap_or_else(|| board.default_rook_file(SpecialMove::CastlingRight)),
+            ),
+            'Q' => (
+                SpecialMove::CastlingLeft,
+                board
+                    .outermost_rook(color, SpecialMove::CastlingLeft)
+                    .unwrap_or_else(|| board.default_rook_file(SpecialMove::CastlingLeft)),
+            ),
+            file @ 'A'..='P' => {
+                let rook_file = file as usize - 'A' as usize;
+                let king_file = board
+                    .king_position(color)
+                    .filter(|king_pos| king_pos.rank() == board.home_rank(color))?
+                    .file();
+                match rook_file {
+                    _ if rook_file >= board.dims.files() => return None,
+                    _ if rook_file > king_file => (SpecialMove::CastlingRight, rook_file),
+                    _ if rook_file < king_file => (SpecialMove::CastlingLeft, rook_file),
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+        if rights.allows(color, side) {
+            return None;
+        }
+        rights.grant(color, side);
+        let side_index = if side == SpecialMove::CastlingRight {
+            1
+        } else {
+            0
+        };
+        board.castling_rooks[color_index(color)][side_index] = rook_file;
+    }
+    Some(rights)
+}
+
+impl ChessBoard {
+    /// Reads the board part of a FEN position, see `parse_fen`.
+    pub fn from_fen(fen: &str) -> Result<ChessBoard, ChessError> {
+        parse_fen(fen).map(|(board, ..)| board)
+    }
+
+    pub fn to_fen(&self, to_move: Color, halfmove_clock: usize, fullmove_number: usize) -> String {
+        let mut placement = String::new();
+        for rank in (0..self.dims.ranks()).rev() {
+            let mut empty = 0;
+            for file in 0..self.dims.files() {
+                if let Some(piece) = self.ref_piece(square(file, rank)) {
+                    if empty > 0 {
+                        placement.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+                    let letter = piece.piece_type.letter();
+                    placement.push(if piece.color == Color::White {
+                        letter
+                    } else {
+                        letter.to_ascii_lowercase()
+                    });
+                } else {
+                    empty += 1;
+                }
+            }
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                placement.push('/');
+            }
+        }
+        format!(
+            "{} {} {} {} {} {}",
+            placement,
+            if to_move == Color::White { "w" } else { "b" },
+            self.castling_field(),
+            self.en_passant_square()
+                .map(|passant_pos| passant_pos.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            halfmove_clock,
+            fullmove_number
+        )
+    }
+
+    /// The castling rights as in X-FEN: "KQkq" unless a castling rook is not
+    /// the outermost one on its side, which is then given by its file.
+    fn castling_field(&self) -> String {
+        let mut field = String::new();
+        for &color in &[Color::White, Color::Black] {
+            for &(side, letter) in &[
+                (SpecialMove::CastlingRight, 'K'),
+                (SpecialMove::CastlingLeft, 'Q'),
+            ] {
+                if !self.castling_rights.allows(color, side) {
+                    continue;
+                }
+                let (_, (rook_pos, _)) = self.castling_targets(color, side);
+                let outermost = self
+                    .outermost_rook(color, side)
+                    .unwrap_or_else(|| self.default_rook_file(side));
+                let letter = if rook_pos.file() == outermost {
+                    letter
+                } else {
+                    (b'A' + rook_pos.file() as u8) as char
+                };
+                field.push(if color == Color::White {
+                    letter
+                } else {
+                    letter.to_ascii_lowercase()
+                });
+            }
+        }
+        if field.is_empty() {
+            field.push('-');
+        }
+        field
+    }
+}
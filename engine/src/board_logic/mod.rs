@@ -1,14 +1,43 @@
 use super::piece_logic::*;
+use crate::move_logic::*;
+use crate::ChessError;
+use bitboard::*;
+use std::{fmt, str::FromStr, sync::Arc};
 
-pub type Board = [[Option<Piece>; 8]; 8]; //Hardcoded size bad? //flatten to 64, get helper function turn (x,y)-> pos
+/// The pieces rank by rank from the first, each rank from the a-file on.
+pub type Board = Vec<Vec<Option<Piece>>>;
 
+pub mod bitboard;
+mod builder;
+mod chess960;
+mod fen;
+mod perft;
 #[cfg(test)]
 mod tests;
+mod zobrist;
+
+pub use builder::PositionBuilder;
+pub use chess960::chess960_back_rank;
+pub use fen::{join_check_counters, parse_fen, parse_fen_with_pieces, split_check_counters};
+#[derive(PartialEq, Clone, Debug)]
 pub struct ChessBoard {
     board: Board,
-    white_king: (usize, usize),
-    black_king: (usize, usize),
-    passant_connection: Option<((usize, usize), (usize, usize))>,
+    dims: Dimensions,
+    /// Where the pieces of each color and type are, kept in step with `board`.
+    bitboards: [[Bitboard; PIECE_KINDS]; 2],
+    passant_connection: Option<(Square, Square)>,
+    castling_rights: CastlingRights,
+    /// The files of the rooks each color castles with, queen side first.
+    castling_rooks: [[usize; 2]; 2],
+    /// Whether castling moves are given as the king taking its own rook, the
+    /// way Chess960 positions need them.
+    chess960: bool,
+    /// See `zobrist_key`.
+    zobrist: u64,
+    /// The custom pieces that may appear on the board, by letter.
+    custom_pieces: Vec<Arc<PieceDefinition>>,
+    /// The pieces a pawn may promote to, queen first in standard chess.
+    promotion_choices: Vec<PieceType>,
 }
 
 impl ChessBoard {
@@ -16,284 +45,650 @@ impl ChessBoard {
         self.board.clone()
     }
 
-    fn add_piece(&mut self, piece: Piece, position: (usize, usize)) {
-        if self.board[position.1][position.0].is_none() {
-            if piece.piece_type == PieceType::King {
-                if piece.color == Color::White && self.white_king == (256, 256) {
-                    self.white_king = position;
-                    self.board[position.1][position.0] = Some(piece);
-                } else if piece.color == Color::Black && self.black_king == (256, 256) {
-                    self.black_king = position;
-                    self.board[position.1][position.0] = Some(piece);
-                } else {
-                    panic!("Error adding King to table!")
+    pub fn dimensions(&self) -> Dimensions {
+        self.dims
+    }
+
+    /// Makes pieces of `definition` readable from FEN and algebraic
+    /// notation on this board, returning their type. Placing a custom piece
+    /// defines it too.
+    pub fn define_piece(&mut self, definition: PieceDefinition) -> Result<PieceType, ChessError> {
+        match self.piece_type_for(definition.letter()) {
+            Some(PieceType::Custom(known)) if *known == definition => Ok(PieceType::Custom(known)),
+            Some(other) => Err(ChessError::InvalidPieceDefinition(format!(
+                "{} cannot use '{}', the letter of the {}",
+                definition.name(),
+                definition.letter(),
+                match &other {
+                    PieceType::Custom(known) => known.name().to_string(),
+                    standard => format!("{:?}", standard),
                 }
-            } else {
-                self.board[position.1][position.0] = Some(piece);
+            ))),
+            None => {
+                let definition = Arc::new(definition);
+                self.custom_pieces.push(definition.clone());
+                Ok(PieceType::Custom(definition))
+            }
+        }
+    }
+
+    /// The standard or custom piece written as `letter`, in either case.
+    pub fn piece_type_for(&self, letter: char) -> Option<PieceType> {
+        PieceType::from_letter(letter).or_else(|| {
+            self.custom_pieces
+                .iter()
+                .find(|definition| definition.letter() == letter.to_ascii_uppercase())
+                .map(|definition| PieceType::Custom(definition.clone()))
+        })
+    }
+
+    pub fn custom_pieces(&self) -> &[Arc<PieceDefinition>] {
+        &self.custom_pieces
+    }
+
+    pub fn promotion_choices(&self) -> &[PieceType] {
+        &self.promotion_choices
+    }
+
+    /// Lets pawns promote to `choices` instead of queen, rook, bishop and
+    /// knight, defining the custom pieces among them.
+    pub fn set_promotion_choices(&mut self, choices: Vec<PieceType>) {
+        for choice in &choices {
+            self.remember_piece(choice);
+        }
+        self.promotion_choices = choices;
+    }
+
+    /// Keeps a custom piece's letter readable on this board.
+    fn remember_piece(&mut self, piece_type: &PieceType) {
+        if let PieceType::Custom(definition) = piece_type {
+            if self.piece_type_for(definition.letter()).is_none() {
+                self.custom_pieces.push(definition.clone());
             }
+        }
+    }
+
+    fn add_piece(&mut self, piece: Piece, position: Square) {
+        if self.ref_piece(position).is_none() {
+            if piece.piece_type == PieceType::King && self.king_position(piece.color).is_some() {
+                panic!("Error adding King to table!")
+            }
+            self.set_space(position, Some(piece));
         } else {
-            panic!("Tried to add piece at non-empty space at {:?}", position)
+            panic!(
+                "Tried to add piece at non-empty space at {:?}",
+                position.coords()
+            )
         }
     }
 
     pub fn promote(
         &mut self,
-        position: (usize, usize),
+        position: Square,
         piece_type: PieceType,
-    ) -> Result<String, String> {
+    ) -> Result<String, ChessError> {
         if let Some(piece) = self.ref_piece(position) {
             if piece.piece_type == PieceType::Pawn
-                && position.1 == if piece.color == Color::White { 7 } else { 0 }
-                && piece_type != PieceType::King
+                && position.rank() == self.home_rank(piece.color.opposite())
+                && self.promotion_choices.contains(&piece_type)
             {
-                self.board[position.1][position.0] = Some(piece_make(piece.color, piece_type));
+                self.set_space(position, Some(piece_make(piece.color, piece_type)));
                 Ok(format!(
-                    "Promoted piece at {:?} to {}",
+                    "Promoted piece at {} to {}",
                     position,
                     self.ref_piece(position).unwrap()
                 ))
             } else {
-                Err("Tried to promote unit at wrong place or of wrong type".to_string())
+                Err(ChessError::InvalidPromotion(position))
             }
         } else {
-            Err("Tried to promote an empty space!".to_string())
+            Err(ChessError::EmptySquare(position))
         }
     }
 
-    pub fn move_piece(
-        &mut self,
-        position: (usize, usize),
-        mov: (usize, usize),
-    ) -> Result<String, String> {
+    /// Plays a legal move and returns it in standard algebraic notation, or
+    /// with " Promotion" appended when a pawn reached the last rank without
+    /// a piece to promote to.
+    pub fn move_piece(&mut self, mov: Move) -> Result<String, ChessError> {
+        let chosen = self.legal_move(mov)?;
+        let piece = self.ref_piece(chosen.from).expect("No piece to move");
+        let color = piece.color;
+        let promotes = piece.piece_type == PieceType::Pawn
+            && (chosen.to.rank() == 0 || chosen.to.rank() == self.dims.ranks() - 1);
+        let mut san = self.san(&chosen);
+        self.make_move(&chosen);
+        if promotes && chosen.promotion.is_none() {
+            return Ok(format!("{} Promotion", san));
+        }
+        if self.is_checkmate(color.opposite()) {
+            san.push('#');
+        } else if self.is_checked(color.opposite()) {
+            san.push('+');
+        }
+        Ok(san)
+    }
+
+    /// The move of `get_moves` that `mov` names, with its kind filled in
+    /// and keeping its promotion, which may be `None` for a pawn reaching
+    /// the last rank.
+    pub fn legal_move(&self, mov: Move) -> Result<Move, ChessError> {
+        let position = mov.from;
+        if let Some(outside) = [mov.from, mov.to]
+            .iter()
+            .find(|square| !self.dims.contains(**square))
+        {
+            return Err(ChessError::OutOfBounds((
+                outside.file() as isize,
+                outside.rank() as isize,
+            )));
+        }
         if self.ref_piece(position).is_none() {
-            return Err("Tried to move empty space!".to_string());
+            return Err(ChessError::EmptySquare(position));
         }
         let mut possible_moves = self.get_moves(position);
-        possible_moves.retain(|(move_, _)| *move_ == mov);
-        if !possible_moves.is_empty() {
-            let (movement, special_move) = possible_moves.pop().unwrap();
-            if let Some(special_move) = special_move {
-                match special_move {
-                    SpecialMove::Pawn2Step => {
-                        let (pos_x, pos_y) = movement;
-                        self.force_move(position, movement)?;
-                        self.passant_connection = Some(((pos_x, pos_y - 1), (pos_x, pos_y)));
-
-                        Ok(format!(
-                            "{} {}",
-                            super::to_notation(position).ok().unwrap(),
-                            super::to_notation(mov).ok().unwrap()
-                        ))
-                    }
-                    SpecialMove::CastlingLeft => {
-                        let color = self.ref_piece(position).unwrap().color;
-                        let pos_y = if color == Color::White { 0 } else { 7 };
-                        self.force_move(position, (2, pos_y))?;
-                        self.force_move((0, pos_y), (2, pos_y))?;
-                        Ok("O-O-O".to_string())
-                    }
-                    SpecialMove::CastlingRight => {
-                        let color = self.ref_piece(position).unwrap().color;
-                        let pos_y = if color == Color::White { 0 } else { 7 };
-                        self.force_move(position, (6, pos_y))?;
-                        self.force_move((0, pos_y), (6, pos_y))?;
-                        Ok("O-O".to_string())
-                    }
+        possible_moves.retain(|possible| possible.to == mov.to);
+        let reaches_target = !possible_moves.is_empty();
+        possible_moves
+            .retain(|possible| mov.promotion.is_none() || possible.promotion == mov.promotion);
+        if let Some(possible) = possible_moves.pop() {
+            Ok(Move {
+                promotion: mov.promotion,
+                ..possible
+            })
+        } else if reaches_target {
+            Err(ChessError::IllegalMove {
+                from: position,
+                to: mov.to,
+                reason: "only pawns reaching the last rank can promote".to_string(),
+            })
+        } else {
+            Err(ChessError::IllegalMove {
+                from: position,
+                to: mov.to,
+                reason: "not a legal destination for this piece".to_string(),
+            })
+        }
+    }
+
+    /// Plays a move taken from `get_moves`, leaving a pawn on the last rank
+    /// unpromoted if the move names no piece. Passing the returned `Undo` to
+    /// `unmake_move` takes it back, which is cheaper than copying the board.
+    pub fn make_move(&mut self, mov: &Move) -> Undo {
+        let (position, movement) = (mov.from, mov.to);
+        let captured = match mov.kind {
+            Some(SpecialMove::EnPassant) => self.passant_connection.map(|(_, pawn_pos)| pawn_pos),
+            Some(SpecialMove::CastlingLeft) | Some(SpecialMove::CastlingRight) => None,
+            _ => Some(movement),
+        }
+        .and_then(|captured_pos| Some((captured_pos, self.ref_piece(captured_pos)?.clone())));
+        let undo = Undo {
+            moved: self.ref_piece(position).cloned().expect("No piece to move"),
+            captured,
+            castled_rook: None,
+            passant_connection: self.passant_connection,
+            castling_rights: self.castling_rights,
+            zobrist: self.zobrist,
+        };
+        // en passant is only available on the ply right after the double step
+        if let Some(file) = self.passant_file() {
+            self.zobrist ^= zobrist::passant_key(file);
+        }
+        let passant_connection = self.passant_connection.take();
+        self.update_castling_rights(position, movement);
+        match mov.kind {
+            Some(SpecialMove::Pawn2Step) => {
+                self.force_move(position, movement).unwrap();
+                let passant_pos = square(movement.file(), (position.rank() + movement.rank()) / 2);
+                self.passant_connection = Some((passant_pos, movement));
+                if let Some(file) = self.passant_file() {
+                    self.zobrist ^= zobrist::passant_key(file);
                 }
-            } else {
-                self.force_move(position, movement)?;
-                let piece = self.ref_piece(movement).unwrap();
-                let mut result = format!(
-                    "{}{} {}",
-                    piece.piece_type,
-                    super::to_notation(position).ok().unwrap(),
-                    super::to_notation(mov).ok().unwrap()
-                );
-                if piece.piece_type == PieceType::Pawn {
-                    if movement.1 == if piece.color == Color::White { 7 } else { 0 } {
-                        result = format!(
-                            "{} {} Promotion",
-                            super::to_notation(position).ok().unwrap(),
-                            super::to_notation(mov).ok().unwrap()
-                        );
-                    }
-                    if let Some((passant_pos, pawn_pos)) = self.passant_connection {
-                        if movement == passant_pos && piece.piece_type == PieceType::Pawn {
-                            self.board[pawn_pos.1][pawn_pos.0] = None;
-                        }
-                    }
+            }
+            Some(special_move @ SpecialMove::CastlingLeft)
+            | Some(special_move @ SpecialMove::CastlingRight) => {
+                let (king_target, (rook_from, rook_to)) =
+                    self.castling_targets(undo.moved.color, special_move);
+                // lift both first, as either may land where the other stood
+                let mut king = self.set_space(position, None).unwrap();
+                let castled_rook = self.set_space(rook_from, None);
+                king.moved();
+                self.set_space(king_target, Some(king));
+                if let Some(mut rook) = castled_rook.clone() {
+                    rook.moved();
+                    self.set_space(rook_to, Some(rook));
+                }
+                self.finish_move(undo.castling_rights);
+                return Undo {
+                    castled_rook,
+                    ..undo
+                };
+            }
+            Some(SpecialMove::EnPassant) => {
+                self.force_move(position, movement).unwrap();
+                if let Some((_, pawn_pos)) = passant_connection {
+                    self.set_space(pawn_pos, None);
+                }
+            }
+            None => {
+                self.force_move(position, movement).unwrap();
+                if let Some(piece_type) = &mov.promotion {
+                    let color = self.ref_piece(movement).unwrap().color;
+                    self.set_space(movement, Some(piece_make(color, piece_type.clone())));
                 }
-                Ok(result)
             }
+        }
+        self.finish_move(undo.castling_rights);
+        undo
+    }
+
+    fn finish_move(&mut self, castling_rights: CastlingRights) {
+        self.zobrist ^= zobrist::castling_key(castling_rights)
+            ^ zobrist::castling_key(self.castling_rights)
+            ^ zobrist::side_key();
+    }
+
+    /// Takes back `mov`, which must be the last move made on the board.
+    pub fn unmake_move(&mut self, mov: &Move, undo: Undo) {
+        if let Some(special_move @ SpecialMove::CastlingLeft)
+        | Some(special_move @ SpecialMove::CastlingRight) = mov.kind
+        {
+            let (king_target, (rook_from, rook_to)) =
+                self.castling_targets(undo.moved.color, special_move);
+            self.set_space(king_target, None);
+            self.set_space(rook_to, None);
+            self.set_space(rook_from, undo.castled_rook);
         } else {
-            Err(format!(
-                "Tried to do illegal move! piece at {:?} cannot move to {:?}",
-                position, mov.0,
-            ))
+            self.set_space(mov.to, None);
+        }
+        self.set_space(mov.from, Some(undo.moved));
+        if let Some((captured_pos, captured)) = undo.captured {
+            self.set_space(captured_pos, Some(captured));
         }
+        self.passant_connection = undo.passant_connection;
+        self.castling_rights = undo.castling_rights;
+        self.zobrist = undo.zobrist;
     }
 
-    pub fn get_moves(
-        &self,
-        position: (usize, usize),
-    ) -> Vec<((usize, usize), Option<SpecialMove>)> {
-        let mut all_moves: Vec<((usize, usize), Option<SpecialMove>)> = Vec::new();
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling_rights
+    }
+
+    pub fn set_castling_rights(&mut self, castling_rights: CastlingRights) {
+        self.zobrist ^=
+            zobrist::castling_key(self.castling_rights) ^ zobrist::castling_key(castling_rights);
+        self.castling_rights = castling_rights;
+    }
+
+    /// Moving the king gives up both its castling rights, and moving from or
+    /// to a castling rook's square the right tied to that rook.
+    fn update_castling_rights(&mut self, from: Square, to: Square) {
+        for &color in &[Color::White, Color::Black] {
+            let king_moves = matches!(
+                self.ref_piece(from),
+                Some(piece) if piece.piece_type == PieceType::King && piece.color == color
+            );
+            for &side in &[SpecialMove::CastlingLeft, SpecialMove::CastlingRight] {
+                let (_, (rook_pos, _)) = self.castling_targets(color, side);
+                if king_moves || from == rook_pos || to == rook_pos {
+                    self.castling_rights.revoke(color, side);
+                }
+            }
+        }
+    }
+
+    /// Every legal move for the piece at `position`, with one move per
+    /// promotion choice when a pawn reaches the last rank.
+    pub fn get_moves(&self, position: Square) -> Vec<Move> {
+        let mut all_moves: Vec<Move> = Vec::new();
+        let (is_pawn, passant) = match self.ref_piece(position) {
+            Some(piece) => (
+                piece.piece_type == PieceType::Pawn,
+                self.passant_capture(piece.color)
+                    .map(|(passant_pos, _)| passant_pos),
+            ),
+            None => return all_moves,
+        };
         for mov in self.regular_moves(position) {
             if !self.self_check(position, mov) {
-                all_moves.push((mov, None));
+                all_moves.push(Move {
+                    kind: if is_pawn && passant == Some(mov) {
+                        Some(SpecialMove::EnPassant)
+                    } else {
+                        None
+                    },
+                    ..Move::new(position, mov)
+                });
             }
         }
-        for mov in self.special_moves(position) {
-            if mov.1 == SpecialMove::Pawn2Step {
-                if !self.self_check(position, mov.0) {
-                    all_moves.push((mov.0, Some(mov.1)));
-                }
-            } else {
-                all_moves.push((mov.0, Some(mov.1)));
+        for (mov, special_move) in self.special_moves(position) {
+            // castling moves are checked for safety when they are generated
+            if special_move == SpecialMove::Pawn2Step && self.self_check(position, mov) {
+                continue;
             }
+            all_moves.push(Move {
+                kind: Some(special_move),
+                ..Move::new(position, mov)
+            });
         }
 
+        if is_pawn {
+            all_moves = all_moves
+                .into_iter()
+                .flat_map(|mov| {
+                    if mov.to.rank() == 0 || mov.to.rank() == self.dims.ranks() - 1 {
+                        self.promotion_choices
+                            .iter()
+                            .map(|piece_type| Move {
+                                promotion: Some(piece_type.clone()),
+                                ..mov.clone()
+                            })
+                            .collect()
+                    } else {
+                        vec![mov]
+                    }
+                })
+                .collect();
+        }
         all_moves
     }
 
-    fn regular_moves(&self, position: (usize, usize)) -> Vec<(usize, usize)> {
-        let mut results: Vec<(usize, usize)> = Vec::new();
-        if let Some(piece) = self.ref_piece(position) {
-            let mut legal_spaces: Vec<((usize, usize), Option<&Piece>)> = Vec::new();
-            match piece.piece_type {
-                PieceType::Pawn => {
-                    let color_modifier = if piece.color == Color::White { 1 } else { -1 };
-                    if let Ok(space) = self.check_move(position, (0, color_modifier)) {
-                        if space.1.is_none() {
-                            legal_spaces.push(space);
-                        }
-                    }
-                    if let Ok(space) = self.check_move(position, (1, color_modifier)) {
-                        if space.1.is_some() && space.1.unwrap().color != piece.color {
-                            legal_spaces.push(space);
-                        }
-                    }
-                    if let Ok(space) = self.check_move(position, (-1, color_modifier)) {
-                        if space.1.is_some() && space.1.unwrap().color != piece.color {
-                            legal_spaces.push(space);
-                        }
-                    }
+    /// Every legal move for `color`, including castling, en passant and
+    /// each promotion choice.
+    pub fn legal_moves(&self, color: Color) -> Vec<Move> {
+        let mut moves: Vec<Move> = Vec::new();
+        for position in self.dims.squares() {
+            if let Some(piece) = self.ref_piece(position) {
+                if piece.color == color {
+                    moves.extend(self.get_moves(position));
                 }
-                _ => {
-                    let (movement1, movement2) = piece.movement;
-                    legal_spaces.extend(self.check_around(
-                        position,
-                        movement1,
-                        piece.moves_continous,
-                    ));
-                    if let Some(movement) = movement2 {
-                        legal_spaces.extend(self.check_around(
-                            position,
-                            movement,
-                            piece.moves_continous,
-                        ));
-                    }
+            }
+        }
+        moves
+    }
+
+    /// The piece `mov` would capture, including a pawn taken en passant.
+    pub fn captured_piece(&self, mov: &Move) -> Option<&Piece> {
+        let piece = self.ref_piece(mov.from)?;
+        let captured_pos = match self.passant_capture(piece.color) {
+            Some((passant_pos, pawn_pos))
+                if piece.piece_type == PieceType::Pawn && passant_pos == mov.to =>
+            {
+                pawn_pos
+            }
+            _ => mov.to,
+        };
+        // a king castling onto its own rook captures nothing
+        self.ref_piece(captured_pos)
+            .filter(|captured| captured.color != piece.color)
+    }
+
+    /// Standard algebraic notation for `mov` in this position, e.g. "Nbd7",
+    /// "exd6" or "e8=Q", without the check or mate suffix.
+    pub fn san(&self, mov: &Move) -> String {
+        let piece = match self.ref_piece(mov.from) {
+            Some(piece) => piece,
+            None => return mov.to_string(),
+        };
+        let kind = self
+            .get_moves(mov.from)
+            .into_iter()
+            .find(|possible| possible.to == mov.to)
+            .and_then(|possible| possible.kind);
+        match kind {
+            Some(SpecialMove::CastlingRight) => return "O-O".to_string(),
+            Some(SpecialMove::CastlingLeft) => return "O-O-O".to_string(),
+            _ => (),
+        }
+        let from = mov.from.to_string();
+        let mut san = String::new();
+        if piece.piece_type != PieceType::Pawn {
+            san.push(piece.piece_type.letter());
+            let mut rivals: Vec<Square> = self
+                .legal_moves(piece.color)
+                .into_iter()
+                .filter(|other| other.to == mov.to && other.from != mov.from)
+                .map(|other| other.from)
+                .filter(|other| {
+                    self.ref_piece(*other).map(|p| &p.piece_type) == Some(&piece.piece_type)
+                })
+                .collect();
+            rivals.dedup();
+            if rivals.iter().all(|other| other.file() != mov.from.file()) {
+                if !rivals.is_empty() {
+                    san.push_str(&from[..1]);
                 }
+            } else if rivals.iter().all(|other| other.rank() != mov.from.rank()) {
+                san.push_str(&from[1..]);
+            } else {
+                san.push_str(&from);
             }
-            for (move_to, space) in legal_spaces {
-                if let Some(p) = space {
-                    if p.color != piece.color {
-                        results.push(move_to);
+        }
+        if self.captured_piece(mov).is_some() {
+            if piece.piece_type == PieceType::Pawn {
+                san.push_str(&from[..1]);
+            }
+            san.push('x');
+        }
+        san.push_str(&mov.to.to_string());
+        if let Some(piece_type) = &mov.promotion {
+            san.push('=');
+            san.push(piece_type.letter());
+        }
+        san
+    }
+
+    /// Reads a move in UCI long algebraic notation like `Move::from_uci`,
+    /// taking the promotion letter from `promotion_choices`, e.g. "b7b8a"
+    /// for an archbishop.
+    pub fn parse_uci(&self, input: &str) -> Result<Move, ChessError> {
+        match input.char_indices().last() {
+            Some((split, letter)) if letter.is_ascii_lowercase() => {
+                let mut mov = Move::from_uci(&input[..split])?;
+                mov.promotion = Some(
+                    self.promotion_choices
+                        .iter()
+                        .find(|piece_type| piece_type.letter().to_ascii_lowercase() == letter)
+                        .cloned()
+                        .ok_or_else(|| ChessError::BadNotation(input.to_string()))?,
+                );
+                Ok(mov)
+            }
+            _ => Move::from_uci(input),
+        }
+    }
+
+    /// Finds the legal move of `color` written in standard algebraic
+    /// notation, e.g. "Nbd7", "exd6", "O-O-O" or "e8=Q+".
+    pub fn parse_san(&self, san: &str, color: Color) -> Result<Move, ChessError> {
+        let bad_notation = || ChessError::BadNotation(san.to_string());
+        let text = san.trim_end_matches(['+', '#', '!', '?']);
+        let mut candidates = self.legal_moves(color);
+        let castling = match text {
+            "O-O" | "0-0" => Some(SpecialMove::CastlingRight),
+            "O-O-O" | "0-0-0" => Some(SpecialMove::CastlingLeft),
+            _ => None,
+        };
+        if castling.is_some() {
+            candidates.retain(|mov| mov.kind == castling);
+        } else {
+            let mut chars: Vec<char> = text.chars().filter(|c| *c != 'x').collect();
+            let mut promotion = None;
+            if let Some(&last) = chars.last() {
+                if last.is_ascii_uppercase() {
+                    promotion = Some(self.piece_type_for(last).ok_or_else(bad_notation)?);
+                    chars.pop();
+                    if chars.last() == Some(&'=') {
+                        chars.pop();
                     }
-                } else {
-                    results.push(move_to);
                 }
             }
+            let piece_type = match chars.first() {
+                Some(letter) if letter.is_ascii_uppercase() => {
+                    let piece_type = self.piece_type_for(*letter).ok_or_else(bad_notation)?;
+                    chars.remove(0);
+                    piece_type
+                }
+                _ => PieceType::Pawn,
+            };
+            // the target square is the last letter and the rank after it
+            let split = chars
+                .iter()
+                .rposition(|c| c.is_ascii_lowercase())
+                .ok_or_else(bad_notation)?;
+            let (from, to) = chars.split_at(split);
+            let to: Square = to
+                .iter()
+                .collect::<String>()
+                .parse()
+                .ok()
+                .filter(|to| self.dims.contains(*to))
+                .ok_or_else(bad_notation)?;
+            let (from_file, from_rank) = match from.split_first() {
+                Some((file, rank)) if file.is_ascii_lowercase() => (Some(*file), rank),
+                _ => (None, from),
+            };
+            let from_file = from_file.map(|file| file as usize - 'a' as usize);
+            let from_rank = if from_rank.is_empty() {
+                None
+            } else {
+                let rank: usize = from_rank
+                    .iter()
+                    .collect::<String>()
+                    .parse()
+                    .map_err(|_| bad_notation())?;
+                Some(rank.checked_sub(1).ok_or_else(bad_notation)?)
+            };
+            candidates.retain(|mov| {
+                mov.to == to
+                    && mov.promotion == promotion
+                    && from_file.is_none_or(|file| mov.from.file() == file)
+                    && from_rank.is_none_or(|rank| mov.from.rank() == rank)
+                    && self.ref_piece(mov.from).map(|piece| &piece.piece_type) == Some(&piece_type)
+            });
         }
+        match candidates.len() {
+            1 => Ok(candidates.remove(0)),
+            0 => Err(ChessError::NoMatchingMove(san.to_string())),
+            _ => Err(ChessError::AmbiguousMove(san.to_string())),
+        }
+    }
+
+    fn regular_moves(&self, position: Square) -> Vec<Square> {
+        let piece = match self.ref_piece(position) {
+            Some(piece) => piece,
+            None => return Vec::new(),
+        };
+        let occupied = self.occupied();
+        let targets = match &piece.piece_type {
+            PieceType::Pawn => {
+                let forward = if piece.color == Color::White { 1 } else { -1 };
+                let push = self
+                    .dims
+                    .offset(position, (0, forward))
+                    .map(|push| bit(self.dims, push))
+                    .filter(|push| push & occupied == 0)
+                    .unwrap_or(0);
+                let passant = self
+                    .passant_capture(piece.color)
+                    .map_or(0, |(passant_pos, _)| bit(self.dims, passant_pos));
+                push | pawn_attacks(self.dims, piece.color, position)
+                    & (self.color_bits(piece.color.opposite()) | passant)
+            }
+            PieceType::Knight => knight_attacks(self.dims, position),
+            PieceType::Bishop => bishop_attacks(self.dims, position, occupied),
+            PieceType::Rook => rook_attacks(self.dims, position, occupied),
+            PieceType::Queen => queen_attacks(self.dims, position, occupied),
+            PieceType::King => king_attacks(self.dims, position),
+            PieceType::Custom(definition) => {
+                let enemies = self.color_bits(piece.color.opposite());
+                definition.movements().iter().fold(0, |targets, movement| {
+                    let reached =
+                        movement_targets(self.dims, movement, piece.color, position, occupied);
+                    let moves = if movement.moves() { !occupied } else { 0 };
+                    let captures = if movement.captures() { enemies } else { 0 };
+                    targets | reached & (moves | captures)
+                })
+            }
+        };
+        let mut results: Vec<Square> =
+            squares(self.dims, targets & !self.color_bits(piece.color)).collect();
         results.sort();
-        results.dedup();
         results
     }
 
-    fn special_moves(&self, position: (usize, usize)) -> Vec<((usize, usize), SpecialMove)> {
-        let mut special_moves: Vec<((usize, usize), SpecialMove)> = Vec::new();
+    fn special_moves(&self, position: Square) -> Vec<(Square, SpecialMove)> {
+        let mut special_moves: Vec<(Square, SpecialMove)> = Vec::new();
         if let Some(piece) = self.ref_piece(position) {
-            if !piece.has_moved {
-                if piece.piece_type == PieceType::Pawn {
-                    let color_modifier = if piece.color == Color::White { 2 } else { -2 };
-                    if let Ok(target_space) = self.check_move(position, (0, color_modifier)) {
-                        if target_space.1.is_none() {
-                            special_moves.push((target_space.0, SpecialMove::Pawn2Step));
-                        }
-                    }
-                }
-                if piece.piece_type == PieceType::King && !self.is_threatened(position, piece.color)
-                {
-                    let color_modifier = if piece.color == Color::White { 0 } else { 7 };
-                    let mut spaces = self.check_continous(position, (1, 0));
-                    if let Some(space) = spaces.pop() {
-                        if let Some(rook) = space.1 {
-                            if rook.piece_type == PieceType::Rook
-                                && rook.color == piece.color
-                                && !rook.has_moved
-                            {
-                                let mut can_castle = true;
-                                for spots in spaces {
-                                    if self.is_threatened(spots.0, piece.color) {
-                                        can_castle = false;
-                                    }
-                                }
-                                if can_castle {
-                                    special_moves
-                                        .push(((6, color_modifier), SpecialMove::CastlingRight));
-                                }
-                            }
-                        }
-                    }
-                    let mut spaces = self.check_continous(position, (-1, 0));
-                    if let Some(space) = spaces.pop() {
-                        if let Some(rook) = space.1 {
-                            if rook.piece_type == PieceType::Rook
-                                && rook.color == piece.color
-                                && !rook.has_moved
-                            {
-                                let mut can_castle = true;
-                                for spots in spaces {
-                                    if self.is_threatened(spots.0, piece.color) {
-                                        can_castle = false;
-                                    }
-                                }
-                                if can_castle {
-                                    special_moves
-                                        .push(((2, color_modifier), SpecialMove::CastlingLeft));
-                                }
-                            }
-                        }
+            if !piece.has_moved && piece.piece_type == PieceType::Pawn {
+                let color_modifier = if piece.color == Color::White { 1 } else { -1 };
+                let passed_space = self.check_move(position, (0, color_modifier));
+                if let (Ok((_, None)), Ok(target_space)) = (
+                    passed_space,
+                    self.check_move(position, (0, 2 * color_modifier)),
+                ) {
+                    // on boards of four ranks that would be a promotion instead
+                    if target_space.1.is_none()
+                        && target_space.0.rank() != self.home_rank(piece.color.opposite())
+                    {
+                        special_moves.push((target_space.0, SpecialMove::Pawn2Step));
                     }
                 }
             }
+            if piece.piece_type == PieceType::King {
+                special_moves.extend(self.castling_moves(position, piece.color));
+            }
         }
         special_moves
     }
 
+    fn castling_moves(&self, position: Square, color: Color) -> Vec<(Square, SpecialMove)> {
+        let mut castling_moves: Vec<(Square, SpecialMove)> = Vec::new();
+        if position.rank() != self.home_rank(color) || self.is_threatened(position, color) {
+            return castling_moves;
+        }
+        for &special_move in &[SpecialMove::CastlingRight, SpecialMove::CastlingLeft] {
+            if !self.castling_rights.allows(color, special_move) {
+                continue;
+            }
+            let (king_target, (rook_from, rook_to)) = self.castling_targets(color, special_move);
+            let has_rook = matches!(
+                self.ref_piece(rook_from),
+                Some(rook) if rook.piece_type == PieceType::Rook && rook.color == color
+            ) && (rook_from.file() > position.file())
+                == (special_move == SpecialMove::CastlingRight);
+            // king and rook may pass each other, but nothing else may be in the way
+            let occupied = self.occupied() & !bit(self.dims, position) & !bit(self.dims, rook_from);
+            let span = |from: Square, to: Square| {
+                let rank = from.rank();
+                (from.file().min(to.file())..=from.file().max(to.file()))
+                    .map(move |file| square(file, rank))
+            };
+            if has_rook
+                && span(position, king_target)
+                    .chain(span(rook_from, rook_to))
+                    .all(|passed| occupied & bit(self.dims, passed) == 0)
+                && span(position, king_target)
+                    .all(|passed| self.attackers(passed, color.opposite(), occupied) == 0)
+            {
+                let target = if self.chess960 {
+                    rook_from
+                } else {
+                    king_target
+                };
+                castling_moves.push((target, special_move));
+            }
+        }
+        castling_moves
+    }
+
     fn check_around(
         &self,
-        position: (usize, usize),
+        position: Square,
         moveset: (isize, isize),
         moves_continous: bool,
-    ) -> Vec<((usize, usize), Option<&Piece>)> {
-        let mut legal_spaces: Vec<((usize, usize), Option<&Piece>)> = Vec::new();
-        let (move_x, move_y) = moveset;
-        let directions = if move_x == 0 || move_y == 0 {
-            [
-                (move_x, move_y),
-                (move_x, -move_y),
-                (-move_y, move_x),
-                (move_y, move_x),
-            ]
-        } else {
-            [
-                (move_x, move_y),
-                (move_x, -move_y),
-                (-move_x, move_y),
-                (-move_x, -move_y),
-            ]
-        };
+    ) -> Vec<(Square, Option<&Piece>)> {
+        let mut legal_spaces: Vec<(Square, Option<&Piece>)> = Vec::new();
+        let directions = symmetric_steps(moveset);
         for direction in directions.iter() {
             if moves_continous {
                 legal_spaces.extend(self.check_continous(position, *direction));
@@ -306,204 +701,563 @@ impl ChessBoard {
 
     fn check_continous(
         &self,
-        position: (usize, usize),
+        position: Square,
         direction: (isize, isize),
-    ) -> Vec<((usize, usize), Option<&Piece>)> {
-        let mut legal_spaces: Vec<((usize, usize), Option<&Piece>)> = Vec::new();
+    ) -> Vec<(Square, Option<&Piece>)> {
+        let mut legal_spaces: Vec<(Square, Option<&Piece>)> = Vec::new();
         if let Ok(target_space) = self.check_move(position, direction) {
             legal_spaces.push(target_space);
             if target_space.1.is_none() {
                 legal_spaces.extend(self.check_continous(target_space.0, direction));
             }
-            if self.passant_connection.is_some()
-                && self.passant_connection.unwrap().0 == target_space.0
-            {
-                legal_spaces.extend(self.check_continous(target_space.0, direction));
-            }
         }
         legal_spaces
     }
 
     fn check_move(
         &self,
-        position: (usize, usize),
+        position: Square,
         moves: (isize, isize),
-    ) -> Result<((usize, usize), Option<&Piece>), String> {
-        let new_x = position.0 as isize + moves.0;
-        let new_y = position.1 as isize + moves.1;
-        if new_x < 0 || new_x >= 8 || new_y < 0 || new_y >= 8 {
-            return Err("not valid movement".to_string());
-        }
-        let new_pos = (new_x as usize, new_y as usize);
-        let target_space = self.ref_piece(new_pos);
-        if let Some(connection) = self.passant_connection {
-            if new_pos == connection.0 {
-                let target_space = self.ref_piece(connection.1);
-                return Ok((new_pos, target_space));
-            }
-        }
-        if target_space.is_some() {
-            Ok((new_pos, target_space))
-        } else {
-            Ok((new_pos, None))
-        }
+    ) -> Result<(Square, Option<&Piece>), ChessError> {
+        let new_pos = self
+            .dims
+            .offset(position, moves)
+            .ok_or(ChessError::OutOfBounds((
+                position.file() as isize + moves.0,
+                position.rank() as isize + moves.1,
+            )))?;
+        Ok((new_pos, self.ref_piece(new_pos)))
     }
 
-    fn self_check(&self, move_from: (usize, usize), move_to: (usize, usize)) -> bool {
-        let mut test = self.clone_chess();
-        let piece = test.ref_piece(move_from).unwrap();
-        let color = piece.color;
-        test.force_move(move_from, move_to)
-            .expect("Error during checking for self-check:");
-        test.is_checked(color)
+    /// The en passant square and the pawn that can be captured there by a
+    /// pawn of `color`, if the opponent just made a double step.
+    fn passant_capture(&self, color: Color) -> Option<(Square, Square)> {
+        self.passant_connection.filter(|(_, pawn_pos)| {
+            self.ref_piece(*pawn_pos).map(|pawn| pawn.color) == Some(color.opposite())
+        })
     }
 
-    fn force_move(
-        &mut self,
-        piece_pos: (usize, usize),
-        new_pos: (usize, usize),
-    ) -> Result<String, String> {
-        if let Some(mut piece) = self.board[piece_pos.1][piece_pos.0].take() {
-            if piece.piece_type == PieceType::King {
-                if piece.color == Color::White {
-                    self.white_king = new_pos;
-                } else {
-                    self.black_king = new_pos;
+    pub fn en_passant_square(&self) -> Option<Square> {
+        self.passant_connection.map(|(passant_pos, _)| passant_pos)
+    }
+
+    /// Whether moving the piece at `move_from` to `move_to` would leave its
+    /// own king attacked.
+    fn self_check(&self, move_from: Square, move_to: Square) -> bool {
+        let piece = self.ref_piece(move_from).unwrap();
+        let color = piece.color;
+        let mut occupied = self.occupied() & !bit(self.dims, move_from) | bit(self.dims, move_to);
+        let mut enemies = self.color_bits(color.opposite()) & !bit(self.dims, move_to);
+        if piece.piece_type == PieceType::Pawn {
+            if let Some((passant_pos, pawn_pos)) = self.passant_capture(color) {
+                if move_to == passant_pos {
+                    // the captured pawn leaves its rank too, which can expose the king
+                    occupied &= !bit(self.dims, pawn_pos);
+                    enemies &= !bit(self.dims, pawn_pos);
                 }
             }
-            piece.moved();
-            self.board[new_pos.1][new_pos.0] = Some(piece);
-            Ok(format!("Moved from {:?} to {:?}", piece_pos, new_pos))
-        } else {
-            Err(format!("Can't force move, no piece at {:?}", piece_pos))
         }
+        let king_pos = if piece.piece_type == PieceType::King {
+            move_to
+        } else {
+            match self.king_position(color) {
+                Some(king_pos) => king_pos,
+                None => return false,
+            }
+        };
+        self.attackers(king_pos, color.opposite(), occupied) & enemies != 0
     }
 
-    fn is_threatened(&self, pos: (usize, usize), color: Color) -> bool {
-        let mut direction = (0, 1);
-        if self.is_threatened_by(pos, color, direction, true) {
-            return true;
+    /// The pieces of `color` attacking `position` when the squares in
+    /// `occupied` block sliding pieces.
+    fn attackers(&self, position: Square, color: Color, occupied: Bitboard) -> Bitboard {
+        if !self.dims.contains(position) {
+            return 0;
         }
-        direction = (1, 1);
-        if self.is_threatened_by(pos, color, direction, true) {
-            return true;
-        }
-        direction = (1, 2);
-        if self.is_threatened_by(pos, color, direction, false) {
-            return true;
+        let pieces =
+            |piece_type: PieceType| self.bitboards[color_index(color)][piece_index(&piece_type)];
+        let diagonal = pieces(PieceType::Bishop) | pieces(PieceType::Queen);
+        let straight = pieces(PieceType::Rook) | pieces(PieceType::Queen);
+        // custom pieces need not move the same way back, so each is asked
+        let custom = squares(self.dims, self.bitboards[color_index(color)][CUSTOM_INDEX])
+            .filter(|from| self.attacks_from(*from, occupied) & bit(self.dims, position) != 0)
+            .fold(0, |custom, from| custom | bit(self.dims, from));
+        (pawn_attacks(self.dims, color.opposite(), position) & pieces(PieceType::Pawn))
+            | (knight_attacks(self.dims, position) & pieces(PieceType::Knight))
+            | (king_attacks(self.dims, position) & pieces(PieceType::King))
+            | (bishop_attacks(self.dims, position, occupied) & diagonal)
+            | (rook_attacks(self.dims, position, occupied) & straight)
+            | custom
+    }
+
+    /// The squares the piece on `position` attacks when the squares in
+    /// `occupied` hold pieces, whoever stands on them.
+    fn attacks_from(&self, position: Square, occupied: Bitboard) -> Bitboard {
+        let piece = match self.ref_piece(position) {
+            Some(piece) => piece,
+            None => return 0,
+        };
+        match &piece.piece_type {
+            PieceType::Pawn => pawn_attacks(self.dims, piece.color, position),
+            PieceType::Knight => knight_attacks(self.dims, position),
+            PieceType::Bishop => bishop_attacks(self.dims, position, occupied),
+            PieceType::Rook => rook_attacks(self.dims, position, occupied),
+            PieceType::Queen => queen_attacks(self.dims, position, occupied),
+            PieceType::King => king_attacks(self.dims, position),
+            PieceType::Custom(definition) => definition
+                .movements()
+                .iter()
+                .filter(|movement| movement.captures())
+                .fold(0, |attacks, movement| {
+                    attacks | movement_targets(self.dims, movement, piece.color, position, occupied)
+                }),
         }
-        direction = (2, 1);
-        if self.is_threatened_by(pos, color, direction, false) {
-            return true;
+    }
+
+    fn force_move(&mut self, piece_pos: Square, new_pos: Square) -> Result<String, ChessError> {
+        if let Some(mut piece) = self.set_space(piece_pos, None) {
+            piece.moved();
+            self.set_space(new_pos, Some(piece));
+            Ok(format!("Moved from {} to {}", piece_pos, new_pos))
+        } else {
+            Err(ChessError::EmptySquare(piece_pos))
         }
-        false
+    }
+
+    fn is_threatened(&self, pos: Square, color: Color) -> bool {
+        self.attackers(pos, color.opposite(), self.occupied()) != 0
     }
 
     pub fn is_threatened_by(
         &self,
-        pos: (usize, usize),
+        pos: Square,
         color: Color,
         moveset: (isize, isize),
         check_continous: bool,
     ) -> bool {
+        !self
+            .threats_along(pos, color, moveset, check_continous)
+            .is_empty()
+    }
+
+    /// Every enemy of `color` reached from `pos` along `moveset` that attacks
+    /// `pos`, looking in all directions the moveset allows.
+    pub fn threats_along(
+        &self,
+        pos: Square,
+        color: Color,
+        moveset: (isize, isize),
+        check_continous: bool,
+    ) -> Vec<Square> {
+        let mut threats = Vec::new();
         for spaces in self.check_around(pos, moveset, check_continous) {
             if let (p_pos, Some(piece)) = spaces {
-                if piece.color != color {
-                    for mov in self.regular_moves(p_pos) {
-                        if mov == pos {
-                            return true;
-                        }
+                if piece.color != color && piece.piece_type == PieceType::Pawn {
+                    // pawns attack diagonally forward even when the space is empty
+                    if pawn_attacks(self.dims, piece.color, p_pos) & bit(self.dims, pos) != 0 {
+                        threats.push(p_pos);
                     }
+                } else if piece.color != color && self.regular_moves(p_pos).contains(&pos) {
+                    threats.push(p_pos);
                 }
             }
         }
-        false
+        threats.sort();
+        threats.dedup();
+        threats
     }
 
-    pub fn is_checked(&self, color: Color) -> bool {
-        let king_pos = if color == Color::White {
-            self.white_king
-        } else {
-            self.black_king
-        };
-        self.is_threatened(king_pos, color)
+    /// The pieces of `color` attacking `position`, whatever stands on it.
+    pub fn attackers_of(&self, position: Square, color: Color) -> Vec<Square> {
+        squares(self.dims, self.attackers(position, color, self.occupied())).collect()
     }
 
-    pub fn is_checkmate(&self, color: Color) -> bool {
-        let king_pos = if color == Color::White {
-            self.white_king
-        } else {
-            self.black_king
+    /// Whether a piece of `color` could recapture on `position`.
+    pub fn is_square_defended(&self, position: Square, color: Color) -> bool {
+        self.attackers(position, color, self.occupied()) != 0
+    }
+
+    /// Every square some piece of `color` attacks, from a1 upwards.
+    pub fn attacked_squares(&self, color: Color) -> Vec<Square> {
+        let occupied = self.occupied();
+        let attacked = squares(self.dims, self.color_bits(color)).fold(0, |attacked, position| {
+            attacked | self.attacks_from(position, occupied)
+        });
+        squares(self.dims, attacked).collect()
+    }
+
+    /// The pieces giving check to the king of `color`.
+    pub fn checkers(&self, color: Color) -> Vec<Square> {
+        match self.king_position(color) {
+            Some(king_pos) => self.attackers_of(king_pos, color.opposite()),
+            None => Vec::new(),
+        }
+    }
+
+    /// The pieces of `color` that may not leave the line between their king
+    /// and an enemy bishop, rook or queen.
+    pub fn pinned_pieces(&self, color: Color) -> Vec<Pin> {
+        let king_pos = match self.king_position(color) {
+            Some(king_pos) => king_pos,
+            None => return Vec::new(),
         };
-        if self.is_threatened(king_pos, color) {
-            if !self.get_moves(king_pos).is_empty() {
-                return false;
-            }
-            for y in 0..self.ref_board().len() {
-                for x in 0..self.ref_board()[0].len() {
-                    if let Some(piece) = self.ref_piece((x, y)) {
-                        if piece.color == color && !self.get_moves((x, y)).is_empty() {
-                            return false;
+        let lines = [
+            (ROOK_DIRECTIONS, PieceType::Rook),
+            (BISHOP_DIRECTIONS, PieceType::Bishop),
+        ];
+        let mut pins = Vec::new();
+        for (directions, slider) in lines.iter() {
+            for direction in directions.iter() {
+                let mut ray = Vec::new();
+                let mut pinned = None;
+                let mut current = king_pos;
+                while let Some(next) = self.dims.offset(current, *direction) {
+                    current = next;
+                    match self.ref_piece(next) {
+                        None => ray.push(next),
+                        Some(piece) if piece.color == color && pinned.is_none() => {
+                            pinned = Some(next)
+                        }
+                        Some(piece) => {
+                            let pins_line =
+                                piece.piece_type == *slider || piece.piece_type == PieceType::Queen;
+                            if let Some(pinned) =
+                                pinned.filter(|_| piece.color != color && pins_line)
+                            {
+                                ray.push(next);
+                                pins.push(Pin {
+                                    pinned,
+                                    pinner: next,
+                                    ray,
+                                });
+                            }
+                            break;
                         }
                     }
                 }
             }
-            return true;
+        }
+        pins.extend(self.custom_pins(color, king_pos));
+        pins.sort_by_key(|pin| pin.pinned);
+        pins
+    }
+
+    /// Pins by enemy custom pieces, which would attack the king if the
+    /// pinned piece were gone, be it along a line or by hopping.
+    fn custom_pins(&self, color: Color, king_pos: Square) -> Vec<Pin> {
+        let occupied = self.occupied();
+        let king = bit(self.dims, king_pos);
+        let own = self.color_bits(color) & !king;
+        let mut pins = Vec::new();
+        let pinners = self.bitboards[color_index(color.opposite())][CUSTOM_INDEX];
+        for pinner in squares(self.dims, pinners) {
+            if self.attacks_from(pinner, occupied) & king != 0 {
+                continue;
+            }
+            for pinned in squares(self.dims, own) {
+                let without = occupied & !bit(self.dims, pinned);
+                if self.attacks_from(pinner, without) & king == 0 {
+                    continue;
+                }
+                let mut ray: Vec<Square> = self
+                    .dims
+                    .squares()
+                    .filter(|to| {
+                        let moved = without | bit(self.dims, *to);
+                        occupied & bit(self.dims, *to) == 0
+                            && self.attacks_from(pinner, moved) & king == 0
+                    })
+                    .collect();
+                ray.push(pinner);
+                pins.push(Pin {
+                    pinned,
+                    pinner,
+                    ray,
+                });
+            }
+        }
+        pins
+    }
+
+    pub fn king_position(&self, color: Color) -> Option<Square> {
+        squares(
+            self.dims,
+            self.bitboards[color_index(color)][piece_index(&PieceType::King)],
+        )
+        .next()
+    }
+
+    pub fn is_checked(&self, color: Color) -> bool {
+        match self.king_position(color) {
+            Some(king_pos) => self.is_threatened(king_pos, color),
+            None => false,
+        }
+    }
+
+    pub fn is_checkmate(&self, color: Color) -> bool {
+        if let Some(king_pos) = self.king_position(color) {
+            if self.is_threatened(king_pos, color) {
+                if !self.get_moves(king_pos).is_empty() {
+                    return false;
+                }
+                return self.legal_moves(color).is_empty();
+            }
         }
         false
     }
 
+    pub fn is_stalemate(&self, color: Color) -> bool {
+        !self.is_checked(color) && self.legal_moves(color).is_empty()
+    }
+
+    /// Neither side can possibly checkmate: only kings remain, plus at most a
+    /// single knight or bishop, or any number of bishops all on one square color.
+    pub fn has_insufficient_material(&self) -> bool {
+        let mut knights = 0;
+        let mut bishop_squares = Vec::new();
+        for position in self.dims.squares() {
+            if let Some(piece) = self.ref_piece(position) {
+                match piece.piece_type {
+                    PieceType::King => (),
+                    PieceType::Knight => knights += 1,
+                    PieceType::Bishop => {
+                        bishop_squares.push((position.file() + position.rank()) % 2);
+                    }
+                    _ => return false,
+                }
+            }
+        }
+        knights + bishop_squares.len() <= 1
+            || (knights == 0 && bishop_squares.windows(2).all(|pair| pair[0] == pair[1]))
+    }
+
+    /// Sets up the pieces of `color` as in standard chess, on a standard board.
     pub fn standard_pieces(&mut self, color: Color) {
         let mut y = if color == Color::White { 1 } else { 6 };
         for x in 0..8 {
-            self.add_piece(piece_make(color, PieceType::Pawn), (x, y));
+            self.add_piece(piece_make(color, PieceType::Pawn), square(x, y));
         }
         y = if color == Color::White { 0 } else { 7 };
-        self.add_piece(piece_make(color, PieceType::Rook), (0, y));
-        self.add_piece(piece_make(color, PieceType::Knight), (1, y));
-        self.add_piece(piece_make(color, PieceType::Bishop), (2, y));
-        self.add_piece(piece_make(color, PieceType::Queen), (3, y));
-        self.add_piece(piece_make(color, PieceType::King), (4, y));
+        self.add_piece(piece_make(color, PieceType::Rook), square(0, y));
+        self.add_piece(piece_make(color, PieceType::Knight), square(1, y));
+        self.add_piece(piece_make(color, PieceType::Bishop), square(2, y));
+        self.add_piece(piece_make(color, PieceType::Queen), square(3, y));
+        self.add_piece(piece_make(color, PieceType::King), square(4, y));
+        self.add_piece(piece_make(color, PieceType::Bishop), square(5, y));
+        self.add_piece(piece_make(color, PieceType::Knight), square(6, y));
+        self.add_piece(piece_make(color, PieceType::Rook), square(7, y));
+        let mut castling_rights = self.castling_rights;
+        castling_rights.grant(color, SpecialMove::CastlingRight);
+        castling_rights.grant(color, SpecialMove::CastlingLeft);
+        self.set_castling_rights(castling_rights);
+    }
+
+    pub fn ref_board(&self) -> &Board {
+        &self.board
+    }
+
+    /// The piece on `position`, or `None` if it is empty or off the board.
+    pub fn ref_piece(&self, position: Square) -> Option<&Piece> {
+        self.board
+            .get(position.rank())?
+            .get(position.file())?
+            .as_ref()
+    }
+
+    /// The rank the pieces of `color` start on.
+    fn home_rank(&self, color: Color) -> usize {
         if color == Color::White {
-            self.white_king = (4, y)
+            0
         } else {
-            self.black_king = (4, y)
+            self.dims.ranks() - 1
         }
-        self.add_piece(piece_make(color, PieceType::Bishop), (5, y));
-        self.add_piece(piece_make(color, PieceType::Knight), (6, y));
-        self.add_piece(piece_make(color, PieceType::Rook), (7, y));
     }
 
-    fn clone_chess(&self) -> ChessBoard {
-        ChessBoard {
-            board: self.board.clone(),
-            white_king: self.white_king,
-            black_king: self.black_king,
-            passant_connection: self.passant_connection,
+    /// The rank the pawns of `color` start on and may make a double step from.
+    fn pawn_rank(&self, color: Color) -> usize {
+        if color == Color::White {
+            1
+        } else {
+            self.dims.ranks() - 2
         }
     }
 
-    pub fn ref_board(&self) -> &[[Option<Piece>; 8]; 8] {
-        &self.board
+    /// Puts `piece` on `position`, or clears it, returning what stood there.
+    fn set_space(&mut self, position: Square, piece: Option<Piece>) -> Option<Piece> {
+        if let Some(piece) = &piece {
+            self.zobrist ^= zobrist::piece_key(piece, position);
+            self.remember_piece(&piece.piece_type);
+        }
+        let previous = std::mem::replace(&mut self.board[position.rank()][position.file()], piece);
+        if let Some(previous) = &previous {
+            self.bitboards[color_index(previous.color)][piece_index(&previous.piece_type)] &=
+                !bit(self.dims, position);
+            self.zobrist ^= zobrist::piece_key(previous, position);
+        }
+        if let Some(piece) = self.ref_piece(position) {
+            self.bitboards[color_index(piece.color)][piece_index(&piece.piece_type)] |=
+                bit(self.dims, position);
+        }
+        previous
+    }
+
+    pub fn color_bits(&self, color: Color) -> Bitboard {
+        self.bitboards[color_index(color)]
+            .iter()
+            .fold(0, |bits, pieces| bits | pieces)
     }
 
-    pub fn ref_piece(&self, position: (usize, usize)) -> Option<&Piece> {
-        self.board[position.1][position.0].as_ref()
+    pub fn occupied(&self) -> Bitboard {
+        self.color_bits(Color::White) | self.color_bits(Color::Black)
     }
 }
 
-#[derive(PartialEq)]
-pub enum SpecialMove {
-    Pawn2Step,
-    CastlingLeft,
-    CastlingRight,
+/// A piece pinned to its king by an enemy slider or custom piece.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Pin {
+    pub pinned: Square,
+    pub pinner: Square,
+    /// The squares between the king and the pinner, the pinner included,
+    /// which the pinned piece may still move to.
+    pub ray: Vec<Square>,
+}
+
+/// What `make_move` changed beyond the moving piece's squares.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Undo {
+    moved: Piece,
+    captured: Option<(Square, Piece)>,
+    castled_rook: Option<Piece>,
+    passant_connection: Option<(Square, Square)>,
+    castling_rights: CastlingRights,
+    zobrist: u64,
+}
+
+/// Which sides each color may still castle to, as in the "KQkq" FEN field.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Default)]
+pub struct CastlingRights {
+    pub white_king_side: bool,
+    pub white_queen_side: bool,
+    pub black_king_side: bool,
+    pub black_queen_side: bool,
+}
+
+impl CastlingRights {
+    pub fn all() -> CastlingRights {
+        CastlingRights {
+            white_king_side: true,
+            white_queen_side: true,
+            black_king_side: true,
+            black_queen_side: true,
+        }
+    }
+
+    pub fn allows(&self, color: Color, side: SpecialMove) -> bool {
+        *self.right(color, side)
+    }
+
+    fn grant(&mut self, color: Color, side: SpecialMove) {
+        *self.right_mut(color, side) = true;
+    }
+
+    fn revoke(&mut self, color: Color, side: SpecialMove) {
+        *self.right_mut(color, side) = false;
+    }
+
+    fn right(&self, color: Color, side: SpecialMove) -> &bool {
+        match (color, side) {
+            (Color::White, SpecialMove::CastlingRight) => &self.white_king_side,
+            (Color::White, _) => &self.white_queen_side,
+            (Color::Black, SpecialMove::CastlingRight) => &self.black_king_side,
+            (Color::Black, _) => &self.black_queen_side,
+        }
+    }
+
+    fn right_mut(&mut self, color: Color, side: SpecialMove) -> &mut bool {
+        match (color, side) {
+            (Color::White, SpecialMove::CastlingRight) => &mut self.white_king_side,
+            (Color::White, _) => &mut self.white_queen_side,
+            (Color::Black, SpecialMove::CastlingRight) => &mut self.black_king_side,
+            (Color::Black, _) => &mut self.black_queen_side,
+        }
+    }
+}
+
+impl fmt::Display for CastlingRights {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rights: String = [
+            (self.white_king_side, 'K'),
+            (self.white_queen_side, 'Q'),
+            (self.black_king_side, 'k'),
+            (self.black_queen_side, 'q'),
+        ]
+        .iter()
+        .filter(|(allowed, _)| *allowed)
+        .map(|(_, letter)| *letter)
+        .collect();
+        if rights.is_empty() {
+            write!(f, "-")
+        } else {
+            write!(f, "{}", rights)
+        }
+    }
+}
+
+impl FromStr for CastlingRights {
+    type Err = ChessError;
+
+    fn from_str(input: &str) -> Result<CastlingRights, ChessError> {
+        let mut rights = CastlingRights::default();
+        if input == "-" {
+            return Ok(rights);
+        }
+        for letter in input.chars() {
+            let right = match letter {
+                'K' => &mut rights.white_king_side,
+                'Q' => &mut rights.white_queen_side,
+                'k' => &mut rights.black_king_side,
+                'q' => &mut rights.black_queen_side,
+                _ => return Err(ChessError::BadNotation(input.to_string())),
+            };
+            if *right {
+                return Err(ChessError::BadNotation(input.to_string()));
+            }
+            *right = true;
+        }
+        if input.is_empty() {
+            return Err(ChessError::BadNotation(input.to_string()));
+        }
+        Ok(rights)
+    }
+}
+
+pub const PROMOTION_CHOICES: [PieceType; 4] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+];
+
+fn square(file: usize, rank: usize) -> Square {
+    Square::new(file, rank).expect("square outside of the board")
 }
 
 pub fn init_board() -> ChessBoard {
+    init_sized_board(Dimensions::STANDARD)
+}
+
+/// An empty board of any size, e.g. 6x6 for Los Alamos chess.
+pub fn init_sized_board(dims: Dimensions) -> ChessBoard {
     ChessBoard {
-        board: Default::default(),
-        white_king: (256, 256),
-        black_king: (256, 256),
+        board: vec![vec![None; dims.files()]; dims.ranks()],
+        dims,
+        bitboards: [[0; PIECE_KINDS]; 2],
         passant_connection: None,
+        castling_rights: CastlingRights::default(),
+        castling_rooks: [[0, dims.files() - 1]; 2],
+        chess960: false,
+        zobrist: 0,
+        custom_pieces: Vec::new(),
+        promotion_choices: PROMOTION_CHOICES.to_vec(),
     }
 }
@@ -0,0 +1,49 @@
+use super::*;
+
+impl ChessBoard {
+    /// Counts the positions `depth` plies ahead with `color` to move, for
+    /// comparing the move generator against known totals.
+    pub fn perft(&self, color: Color, depth: usize) -> u64 {
+        self.clone().count_nodes(color, depth)
+    }
+
+    /// The perft count below each legal move, to find which move the
+    /// generator gets wrong when a total is off.
+    pub fn divide(&self, color: Color, depth: usize) -> Vec<(Move, u64)> {
+        let mut board = self.clone();
+        self.legal_moves(color)
+            .into_iter()
+            .map(|mov| {
+                let nodes = match depth {
+                    0 => 0,
+                    _ => {
+                        let undo = board.make_move(&mov);
+                        let nodes = board.count_nodes(color.opposite(), depth - 1);
+                        board.unmake_move(&mov, undo);
+                        nodes
+                    }
+                };
+                (mov, nodes)
+            })
+            .collect()
+    }
+
+    fn count_nodes(&mut self, color: Color, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.legal_moves(color);
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        moves
+            .iter()
+            .map(|mov| {
+                let undo = self.make_move(mov);
+                let nodes = self.count_nodes(color.opposite(), depth - 1);
+                self.unmake_move(mov, undo);
+                nodes
+            })
+            .sum()
+    }
+}
@@ -1,8 +1,17 @@
 use crate::{board_logic::*, console_display::*, *};
+
+fn sq(file: usize, rank: usize) -> Square {
+    Square::new(file, rank).unwrap()
+}
+
 #[test]
 fn it_translates() {
     assert_eq!(to_coords("a5".to_string()).unwrap(), (0, 4));
     assert_eq!(to_notation((0, 4)).unwrap(), ("a5"));
+    assert!(to_coords("z9".to_string()).is_err());
+    assert!(to_coords("é4".to_string()).is_err());
+    assert_eq!(to_notation((9, 9)).unwrap(), "j10");
+    assert!(to_notation((16, 0)).is_err());
 }
 #[test]
 #[should_panic(expected = "Tried to add piece at non-empty space at (0, 0)")]
@@ -11,7 +20,7 @@ fn occupied_spot() {
     board.standard_pieces(Color::White);
     board.standard_pieces(Color::Black);
     print_board(board.ref_board());
-    board.add_piece(piece_make(Color::Black, PieceType::Pawn), (0, 0));
+    board.add_piece(piece_make(Color::Black, PieceType::Pawn), sq(0, 0));
 }
 
 #[test]
@@ -19,11 +28,13 @@ fn pawn_moves() {
     let mut board: ChessBoard = init_board();
     board.standard_pieces(Color::White);
     board.standard_pieces(Color::Black);
-    board.force_move((4, 6), (4, 2)).expect("force_move panic");
+    board
+        .force_move(sq(4, 6), sq(4, 2))
+        .expect("force_move panic");
     print_board(board.ref_board());
-    let mut moves = board.regular_moves((3, 1));
-    moves.push(board.special_moves((3, 1)).pop().unwrap().0);
-    assert_eq!(moves, vec![(3, 2), (4, 2), (3, 3)])
+    let mut moves = board.regular_moves(sq(3, 1));
+    moves.push(board.special_moves(sq(3, 1)).pop().unwrap().0);
+    assert_eq!(moves, vec![sq(3, 2), sq(4, 2), sq(3, 3)])
 }
 
 #[test]
@@ -31,14 +42,16 @@ fn passant() {
     let mut board: ChessBoard = init_board();
     board.standard_pieces(Color::White);
     board.standard_pieces(Color::Black);
-    board.force_move((4, 6), (4, 3)).expect("force_move panic");
+    board
+        .force_move(sq(4, 6), sq(4, 3))
+        .expect("force_move panic");
     print_board(board.ref_board());
-    board.move_piece((3, 1), (3, 3)).unwrap();
+    board.move_piece(Move::new(sq(3, 1), sq(3, 3))).unwrap();
     print_board(board.ref_board());
-    for mov in board.get_moves((4, 3)) {
-        println!("{:?}", mov.0);
+    for mov in board.get_moves(sq(4, 3)) {
+        println!("{}", mov);
     }
-    board.move_piece((4, 3), (3, 2)).unwrap();
+    board.move_piece(Move::new(sq(4, 3), sq(3, 2))).unwrap();
     print_board(board.ref_board());
 }
 #[test]
@@ -46,8 +59,12 @@ fn continous_moves() {
     let mut board: ChessBoard = init_board();
     board.standard_pieces(Color::White);
     board.standard_pieces(Color::Black);
-    board.force_move((3, 1), (3, 3)).expect("force_move panic");
-    board.force_move((3, 0), (3, 1)).expect("force_move panic");
+    board
+        .force_move(sq(3, 1), sq(3, 3))
+        .expect("force_move panic");
+    board
+        .force_move(sq(3, 0), sq(3, 1))
+        .expect("force_move panic");
     print_board(board.ref_board());
     let mut queen_moves = vec![
         (0, 4),
@@ -61,87 +78,108 @@ fn continous_moves() {
         (7, 5),
     ];
     queen_moves.sort();
-    let mut moves = board.regular_moves((3, 1));
+    let mut moves: Vec<(usize, usize)> = board
+        .regular_moves(sq(3, 1))
+        .into_iter()
+        .map(Square::coords)
+        .collect();
     moves.sort();
     assert_eq!(queen_moves, moves)
 }
 #[test]
 fn castling() {
     let mut board: ChessBoard = init_board();
-    board.add_piece(piece_make(Color::White, PieceType::King), (4, 0));
-    board.add_piece(piece_make(Color::White, PieceType::Knight), (3, 0));
-    board.add_piece(piece_make(Color::White, PieceType::Pawn), (3, 1));
-    board.add_piece(piece_make(Color::White, PieceType::Rook), (7, 0));
-    board.add_piece(piece_make(Color::White, PieceType::Rook), (0, 0));
+    board.add_piece(piece_make(Color::White, PieceType::King), sq(4, 0));
+    board.add_piece(piece_make(Color::White, PieceType::Knight), sq(3, 0));
+    board.add_piece(piece_make(Color::White, PieceType::Pawn), sq(3, 1));
+    board.add_piece(piece_make(Color::White, PieceType::Rook), sq(7, 0));
+    board.add_piece(piece_make(Color::White, PieceType::Rook), sq(0, 0));
     board.standard_pieces(Color::Black);
-    board.force_move((0, 7), (3, 5)).expect("force_move panic");
+    board.set_castling_rights(CastlingRights::all());
+    board
+        .force_move(sq(0, 7), sq(3, 5))
+        .expect("force_move panic");
     print_board(board.ref_board());
-    assert_eq!(board.special_moves((4, 0)).len(), 1);
-    board.force_move((3, 0), (6, 1)).expect("force_move panic");
+    assert_eq!(board.special_moves(sq(4, 0)).len(), 1);
+    board
+        .force_move(sq(3, 0), sq(6, 1))
+        .expect("force_move panic");
     print_board(board.ref_board());
-    assert_eq!(board.special_moves((4, 0)).len(), 2);
-    board.force_move((3, 1), (6, 0)).expect("force_move panic");
+    assert_eq!(board.special_moves(sq(4, 0)).len(), 2);
+    board
+        .force_move(sq(3, 1), sq(6, 0))
+        .expect("force_move panic");
     print_board(board.ref_board());
-    assert_eq!(board.special_moves((4, 0)).len(), 0);
-    board.force_move((6, 0), (6, 2)).expect("force_move panic");
+    assert_eq!(board.special_moves(sq(4, 0)).len(), 0);
+    board
+        .force_move(sq(6, 0), sq(6, 2))
+        .expect("force_move panic");
     print_board(board.ref_board());
-    assert_eq!(board.special_moves((4, 0)).len(), 1);
+    assert_eq!(board.special_moves(sq(4, 0)).len(), 1);
 }
 #[test]
 fn finds_checked() {
     let mut board: ChessBoard = init_board();
-    board.add_piece(piece_make(Color::White, PieceType::King), (3, 0));
-    board.add_piece(piece_make(Color::Black, PieceType::Rook), (3, 3));
+    board.add_piece(piece_make(Color::White, PieceType::King), sq(3, 0));
+    board.add_piece(piece_make(Color::Black, PieceType::Rook), sq(3, 3));
     print_board(board.ref_board());
-    assert_eq!(true, board.is_checked(Color::White))
+    assert!(board.is_checked(Color::White))
 }
 
 #[test]
 fn does_promotion() {
     let mut board: ChessBoard = init_board();
-    board.add_piece(piece_make(Color::White, PieceType::King), (0, 0));
-    board.add_piece(piece_make(Color::Black, PieceType::Pawn), (3, 1));
+    board.add_piece(piece_make(Color::White, PieceType::King), sq(0, 0));
+    board.add_piece(piece_make(Color::Black, PieceType::Pawn), sq(3, 1));
     if board
-        .move_piece((3, 1), (3, 0))
+        .move_piece(Move::new(sq(3, 1), sq(3, 0)))
         .unwrap()
         .contains("Promotion")
     {
-        board.promote((3, 0), PieceType::Queen).unwrap();
+        board.promote(sq(3, 0), PieceType::Queen).unwrap();
     }
     print_board(board.ref_board());
-    assert_eq!(true, board.is_checked(Color::White))
+    assert!(board.is_checked(Color::White))
 }
 #[test]
 fn self_check() {
     let mut board: ChessBoard = init_board();
-    board.add_piece(piece_make(Color::White, PieceType::King), (3, 0));
-    board.add_piece(piece_make(Color::White, PieceType::Knight), (3, 1));
-    board.add_piece(piece_make(Color::Black, PieceType::Pawn), (1, 2));
-    board.add_piece(piece_make(Color::Black, PieceType::Rook), (3, 3));
+    board.add_piece(piece_make(Color::White, PieceType::King), sq(3, 0));
+    board.add_piece(piece_make(Color::White, PieceType::Knight), sq(3, 1));
+    board.add_piece(piece_make(Color::Black, PieceType::Pawn), sq(1, 2));
+    board.add_piece(piece_make(Color::Black, PieceType::Rook), sq(3, 3));
     print_board(board.ref_board());
-    assert_eq!(0, board.get_moves((3, 1)).len())
+    assert_eq!(0, board.get_moves(sq(3, 1)).len())
 }
 
 #[test]
 fn check_mate() {
     let mut board: ChessBoard = init_board();
-    board.add_piece(piece_make(Color::White, PieceType::King), (7, 7));
-    board.add_piece(piece_make(Color::Black, PieceType::Knight), (5, 5));
-    board.add_piece(piece_make(Color::Black, PieceType::Rook), (7, 6));
+    board.add_piece(piece_make(Color::White, PieceType::King), sq(7, 7));
+    board.add_piece(piece_make(Color::Black, PieceType::Knight), sq(5, 5));
+    board.add_piece(piece_make(Color::Black, PieceType::Rook), sq(7, 6));
     print_board(board.ref_board());
-    assert_eq!(true, board.self_check((7, 7), (6, 6)));
-    assert_eq!(true, board.is_checkmate(Color::White));
+    assert!(board.self_check(sq(7, 7), sq(6, 6)));
+    assert!(board.is_checkmate(Color::White));
     let mut board2: ChessBoard = init_board();
     board2.standard_pieces(Color::Black);
     board2.standard_pieces(Color::White);
-    board2.force_move((5, 1), (5, 2)).expect("force_move panic");
-    board2.force_move((4, 6), (4, 4)).expect("force_move panic");
+    board2
+        .force_move(sq(5, 1), sq(5, 2))
+        .expect("force_move panic");
+    board2
+        .force_move(sq(4, 6), sq(4, 4))
+        .expect("force_move panic");
     print_board(board2.ref_board());
-    assert_eq!(false, board2.is_checkmate(Color::White));
-    board2.force_move((6, 1), (6, 3)).expect("force_move panic");
-    board2.force_move((3, 7), (7, 3)).expect("force_move panic");
+    assert!(!board2.is_checkmate(Color::White));
+    board2
+        .force_move(sq(6, 1), sq(6, 3))
+        .expect("force_move panic");
+    board2
+        .force_move(sq(3, 7), sq(7, 3))
+        .expect("force_move panic");
     print_board(board2.ref_board());
-    assert_eq!(true, board2.is_checkmate(Color::White))
+    assert!(board2.is_checkmate(Color::White))
 }
 
 #[test]
@@ -149,8 +187,12 @@ fn finds_mov() {
     let mut board: ChessBoard = init_board();
     board.standard_pieces(Color::White);
     board.standard_pieces(Color::Black);
-    board.force_move((3, 1), (3, 3)).expect("force_move panic");
-    board.force_move((3, 0), (3, 1)).expect("force_move panic");
+    board
+        .force_move(sq(3, 1), sq(3, 3))
+        .expect("force_move panic");
+    board
+        .force_move(sq(3, 0), sq(3, 1))
+        .expect("force_move panic");
     print_board(board.ref_board());
     let mut queen_moves = vec![
         (0, 4),
@@ -164,9 +206,784 @@ fn finds_mov() {
         (7, 5),
     ];
     queen_moves.sort();
-    let mut movs = board.get_moves((3, 1));
-    movs.retain(|(mov, _)| *mov == (1, 3));
-    for (mov, special) in movs {
-        println!("{:?} {}", mov, { special == None });
+    let mut movs = board.get_moves(sq(3, 1));
+    movs.retain(|mov| mov.to == sq(1, 3));
+    for Move {
+        to: mov,
+        kind: special,
+        ..
+    } in movs
+    {
+        println!("{:?} {}", mov, special.is_none());
+    }
+}
+
+#[test]
+fn legal_moves() {
+    let mut board: ChessBoard = init_board();
+    board.standard_pieces(Color::White);
+    board.standard_pieces(Color::Black);
+    assert_eq!(20, board.legal_moves(Color::White).len());
+    assert_eq!(20, board.legal_moves(Color::Black).len());
+}
+
+#[test]
+fn promotion_choices() {
+    let mut board: ChessBoard = init_board();
+    board.add_piece(piece_make(Color::White, PieceType::King), sq(0, 0));
+    board.add_piece(piece_make(Color::White, PieceType::Pawn), sq(3, 6));
+    board.add_piece(piece_make(Color::Black, PieceType::Knight), sq(4, 7));
+    let promotions: Vec<Move> = board
+        .get_moves(sq(3, 6))
+        .into_iter()
+        .filter(|mov| mov.promotion.is_some())
+        .collect();
+    assert_eq!(8, promotions.len());
+    assert!(promotions.contains(&Move {
+        promotion: Some(PieceType::Knight),
+        ..Move::new(sq(3, 6), sq(4, 7))
+    }));
+}
+
+#[test]
+fn stalemate() {
+    let mut board: ChessBoard = init_board();
+    board.add_piece(piece_make(Color::Black, PieceType::King), sq(7, 7));
+    board.add_piece(piece_make(Color::White, PieceType::King), sq(5, 6));
+    board.add_piece(piece_make(Color::White, PieceType::Queen), sq(6, 5));
+    print_board(board.ref_board());
+    assert!(board.is_stalemate(Color::Black));
+    assert!(!board.is_checkmate(Color::Black));
+    assert!(!board.is_stalemate(Color::White));
+}
+
+#[test]
+fn insufficient_material() {
+    let mut board: ChessBoard = init_board();
+    board.add_piece(piece_make(Color::White, PieceType::King), sq(0, 0));
+    board.add_piece(piece_make(Color::Black, PieceType::King), sq(7, 7));
+    assert!(board.has_insufficient_material());
+    board.add_piece(piece_make(Color::White, PieceType::Bishop), sq(2, 0));
+    assert!(board.has_insufficient_material());
+    board.add_piece(piece_make(Color::Black, PieceType::Bishop), sq(5, 7));
+    board.add_piece(piece_make(Color::Black, PieceType::Bishop), sq(3, 5));
+    assert!(board.has_insufficient_material());
+    board.add_piece(piece_make(Color::Black, PieceType::Bishop), sq(2, 7));
+    assert!(!board.has_insufficient_material());
+}
+
+fn passant_kinds(board: &ChessBoard, position: Square) -> Vec<Square> {
+    board
+        .get_moves(position)
+        .into_iter()
+        .filter(|mov| mov.kind == Some(SpecialMove::EnPassant))
+        .map(|mov| mov.to)
+        .collect()
+}
+
+#[test]
+fn passant_black_pawn() {
+    let mut board: ChessBoard = init_board();
+    board.standard_pieces(Color::White);
+    board.standard_pieces(Color::Black);
+    board
+        .force_move(sq(4, 1), sq(4, 4))
+        .expect("force_move panic");
+    board.move_piece(Move::new(sq(3, 6), sq(3, 4))).unwrap();
+    assert_eq!(Some(sq(3, 5)), board.en_passant_square());
+    assert_eq!(vec![sq(3, 5)], passant_kinds(&board, sq(4, 4)));
+    board.move_piece(Move::new(sq(4, 4), sq(3, 5))).unwrap();
+    print_board(board.ref_board());
+    assert!(board.ref_piece(sq(3, 4)).is_none());
+    assert_eq!(
+        Some(Color::White),
+        board.ref_piece(sq(3, 5)).map(|pawn| pawn.color)
+    );
+}
+
+#[test]
+fn passant_expires() {
+    let mut board: ChessBoard = init_board();
+    board.standard_pieces(Color::White);
+    board.standard_pieces(Color::Black);
+    board
+        .force_move(sq(4, 1), sq(4, 4))
+        .expect("force_move panic");
+    board.move_piece(Move::new(sq(3, 6), sq(3, 4))).unwrap();
+    board.move_piece(Move::new(sq(0, 1), sq(0, 2))).unwrap();
+    assert_eq!(None, board.en_passant_square());
+    board.move_piece(Move::new(sq(0, 6), sq(0, 5))).unwrap();
+    assert!(passant_kinds(&board, sq(4, 4)).is_empty());
+    assert!(board.move_piece(Move::new(sq(4, 4), sq(3, 5))).is_err());
+}
+
+#[test]
+fn passant_square_is_empty_for_other_pieces() {
+    let mut board: ChessBoard = init_board();
+    board.standard_pieces(Color::White);
+    board.standard_pieces(Color::Black);
+    board
+        .force_move(sq(3, 1), sq(3, 2))
+        .expect("force_move panic");
+    board
+        .force_move(sq(6, 7), sq(2, 5))
+        .expect("force_move panic");
+    board.move_piece(Move::new(sq(4, 1), sq(4, 3))).unwrap();
+    // the knight just moves to e3, it doesn't capture the pawn on e4
+    let knight_moves = board.get_moves(sq(2, 5));
+    assert!(!knight_moves.iter().any(|mov| mov.to == sq(4, 2)));
+    board.move_piece(Move::new(sq(2, 5), sq(3, 3))).unwrap();
+    board.move_piece(Move::new(sq(7, 1), sq(7, 3))).unwrap();
+    // and a pawn can still step onto the square in front of the double step
+    board
+        .force_move(sq(3, 3), sq(0, 4))
+        .expect("force_move panic");
+    board.move_piece(Move::new(sq(7, 6), sq(7, 4))).unwrap();
+    assert!(board
+        .get_moves(sq(7, 3))
+        .iter()
+        .all(|mov| mov.kind.is_none() && mov.to != sq(7, 4)));
+    assert!(board.ref_piece(sq(4, 3)).is_some());
+}
+
+#[test]
+fn passant_horizontal_pin() {
+    let mut board: ChessBoard = init_board();
+    board.add_piece(piece_make(Color::White, PieceType::King), sq(0, 4));
+    board.add_piece(piece_make(Color::White, PieceType::Pawn), sq(1, 3));
+    board
+        .force_move(sq(1, 3), sq(1, 4))
+        .expect("force_move panic");
+    board.add_piece(piece_make(Color::Black, PieceType::Pawn), sq(2, 6));
+    board.add_piece(piece_make(Color::Black, PieceType::Rook), sq(7, 4));
+    board.add_piece(piece_make(Color::Black, PieceType::King), sq(7, 7));
+    board.move_piece(Move::new(sq(2, 6), sq(2, 4))).unwrap();
+    print_board(board.ref_board());
+    assert!(passant_kinds(&board, sq(1, 4)).is_empty());
+    assert_eq!(
+        vec![sq(1, 5)],
+        board
+            .get_moves(sq(1, 4))
+            .into_iter()
+            .map(|mov| mov.to)
+            .collect::<Vec<_>>()
+    );
+}
+
+fn castling_board() -> ChessBoard {
+    let mut board: ChessBoard = init_board();
+    board.add_piece(piece_make(Color::White, PieceType::King), sq(4, 0));
+    board.add_piece(piece_make(Color::White, PieceType::Rook), sq(0, 0));
+    board.add_piece(piece_make(Color::White, PieceType::Rook), sq(7, 0));
+    board.add_piece(piece_make(Color::Black, PieceType::King), sq(4, 7));
+    board.add_piece(piece_make(Color::Black, PieceType::Rook), sq(0, 7));
+    board.add_piece(piece_make(Color::Black, PieceType::Rook), sq(7, 7));
+    board.set_castling_rights(CastlingRights::all());
+    board
+}
+
+#[test]
+fn castling_moves_rooks() {
+    let mut board = castling_board();
+    assert_eq!(
+        "O-O",
+        board.move_piece(Move::new(sq(4, 0), sq(6, 0))).unwrap()
+    );
+    assert_eq!(
+        Some(PieceType::Rook),
+        board.ref_piece(sq(5, 0)).map(|p| p.piece_type.clone())
+    );
+    assert!(board.ref_piece(sq(7, 0)).is_none());
+    assert_eq!(
+        "O-O-O",
+        board.move_piece(Move::new(sq(4, 7), sq(2, 7))).unwrap()
+    );
+    assert_eq!(
+        Some(PieceType::Rook),
+        board.ref_piece(sq(3, 7)).map(|p| p.piece_type.clone())
+    );
+    assert!(board.ref_piece(sq(0, 7)).is_none());
+    assert_eq!("-", board.castling_rights().to_string());
+}
+
+#[test]
+fn castling_rights_update() {
+    let mut board = castling_board();
+    assert_eq!("KQkq", board.castling_rights().to_string());
+    board.move_piece(Move::new(sq(7, 0), sq(7, 3))).unwrap();
+    board.move_piece(Move::new(sq(7, 3), sq(7, 0))).unwrap();
+    assert_eq!("Qkq", board.castling_rights().to_string());
+    board.move_piece(Move::new(sq(0, 0), sq(0, 7))).unwrap();
+    assert_eq!("k", board.castling_rights().to_string());
+    assert_eq!(Ok(board.castling_rights()), "k".parse::<CastlingRights>());
+    assert!("KK".parse::<CastlingRights>().is_err());
+}
+
+#[test]
+fn castling_through_check() {
+    let mut board = castling_board();
+    board.add_piece(piece_make(Color::Black, PieceType::Bishop), sq(3, 2));
+    print_board(board.ref_board());
+    // the bishop covers f1 which the king passes, and b1 which it doesn't
+    assert_eq!(
+        vec![(sq(2, 0), SpecialMove::CastlingLeft)],
+        board.special_moves(sq(4, 0))
+    );
+    board
+        .force_move(sq(3, 2), sq(3, 5))
+        .expect("force_move panic");
+    assert_eq!(2, board.special_moves(sq(4, 0)).len());
+    board.add_piece(piece_make(Color::Black, PieceType::Pawn), sq(7, 1));
+    // the pawn covers g1 where the king would land
+    assert_eq!(
+        vec![(sq(2, 0), SpecialMove::CastlingLeft)],
+        board.special_moves(sq(4, 0))
+    );
+    board
+        .force_move(sq(3, 5), sq(1, 3))
+        .expect("force_move panic");
+    // no castling out of check
+    assert!(board.special_moves(sq(4, 0)).is_empty());
+}
+
+#[test]
+fn san_disambiguation() {
+    let mut board = castling_board();
+    board.add_piece(piece_make(Color::White, PieceType::Knight), sq(1, 0));
+    board.add_piece(piece_make(Color::White, PieceType::Knight), sq(5, 2));
+    board.add_piece(piece_make(Color::White, PieceType::Queen), sq(7, 3));
+    board.add_piece(piece_make(Color::White, PieceType::Queen), sq(7, 5));
+    board.add_piece(piece_make(Color::White, PieceType::Queen), sq(5, 3));
+    board.add_piece(piece_make(Color::Black, PieceType::Knight), sq(3, 1));
+    assert_eq!("Nbxd2", board.san(&Move::new(sq(1, 0), sq(3, 1))));
+    assert_eq!("Ng5", board.san(&Move::new(sq(5, 2), sq(6, 4))));
+    assert_eq!("Q6h5", board.san(&Move::new(sq(7, 5), sq(7, 4))));
+    assert_eq!("Qh4g5", board.san(&Move::new(sq(7, 3), sq(6, 4))));
+}
+
+#[test]
+fn fen_round_trip() {
+    let mut board = init_board();
+    board.standard_pieces(Color::White);
+    board.standard_pieces(Color::Black);
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    assert_eq!(start, board.to_fen(Color::White, 0, 1));
+    assert_eq!(board, ChessBoard::from_fen(start).unwrap());
+
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let (board, to_move, halfmove_clock, fullmove_number) = parse_fen(kiwipete).unwrap();
+    assert_eq!(
+        kiwipete,
+        board.to_fen(to_move, halfmove_clock, fullmove_number)
+    );
+
+    let passant = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 3";
+    let board = ChessBoard::from_fen(passant).unwrap();
+    assert_eq!(Some(sq(3, 5)), board.en_passant_square());
+    assert!(board
+        .get_moves(sq(4, 4))
+        .iter()
+        .any(|mov| mov.kind == Some(SpecialMove::EnPassant)));
+    assert_eq!(passant, board.to_fen(Color::White, 0, 3));
+    // pawns away from their start rank can't double step
+    let board = ChessBoard::from_fen("4k3/8/8/8/8/P7/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(1, board.get_moves(sq(0, 2)).len());
+}
+
+#[test]
+fn fen_errors() {
+    let reason = |fen: &str| match ChessBoard::from_fen(fen) {
+        Err(ChessError::BadFen(reason)) => reason,
+        other => panic!("expected BadFen, got {:?}", other.map(|_| ())),
+    };
+    assert_eq!("expected 6 fields, found 2", reason("8/8/8/8/8/8/8/8 w"));
+    assert_eq!("expected 4 to 16 ranks, found 3", reason("8/8/8 w - - 0 1"));
+    assert_eq!(
+        "rank 7 has 8 files instead of 7",
+        reason("7/8/8/8/8/8/8/8 w - - 0 1")
+    );
+    assert_eq!(
+        "rank 8 has too many files",
+        reason("ppppppppppppppppp/8/8/8/8/8/8/8 w - - 0 1")
+    );
+    assert_eq!("rank 4 has too many files", reason("17/8/8/8 w - - 0 1"));
+    assert_eq!(
+        "rank 8 has too many files",
+        reason("99999999999999999999999/8/8/8/8/8/8/8 w - - 0 1")
+    );
+    assert_eq!(
+        "cannot play on a board of 12x12 squares",
+        reason("12/12/12/12/12/12/12/12/12/12/12/12 w - - 0 1")
+    );
+    assert_eq!(
+        "unknown piece 'x' on rank 1",
+        reason("8/8/8/8/8/8/8/x7 w - - 0 1")
+    );
+    assert_eq!(
+        "more than one White king",
+        reason("8/8/8/8/8/8/8/K6K w - - 0 1")
+    );
+    assert_eq!(
+        "unknown side to move 'x'",
+        reason("8/8/8/8/8/8/8/8 x - - 0 1")
+    );
+    assert_eq!(
+        "invalid castling rights 'KX'",
+        reason("8/8/8/8/8/8/8/8 w KX - 0 1")
+    );
+    assert_eq!(
+        "no pawn can be captured en passant on e3",
+        reason("8/8/8/8/8/8/8/8 b - e3 0 1")
+    );
+    assert_eq!(
+        "invalid fullmove number '0'",
+        reason("8/8/8/8/8/8/8/8 w - - 0 0")
+    );
+    assert_eq!(
+        "fullmove number too large",
+        reason("4k3/8/8/8/8/8/8/4K3 w - - 0 18446744073709551615")
+    );
+}
+
+#[test]
+fn san_parsing() {
+    let mut board = castling_board();
+    board.add_piece(piece_make(Color::White, PieceType::Knight), sq(1, 0));
+    board.add_piece(piece_make(Color::White, PieceType::Knight), sq(5, 2));
+    board.add_piece(piece_make(Color::White, PieceType::Pawn), sq(6, 6));
+    board.add_piece(piece_make(Color::Black, PieceType::Knight), sq(3, 1));
+    let parse = |san: &str| board.parse_san(san, Color::White);
+    assert_eq!(Ok(Move::new(sq(1, 0), sq(3, 1))), parse("Nbxd2"));
+    assert_eq!(Ok(Move::new(sq(5, 2), sq(3, 1))), parse("N3d2"));
+    assert_eq!(
+        Err(ChessError::AmbiguousMove("Nxd2".to_string())),
+        parse("Nxd2")
+    );
+    assert_eq!(
+        Err(ChessError::NoMatchingMove("O-O-O".to_string())),
+        parse("O-O-O")
+    );
+    assert_eq!(
+        Ok(Some(SpecialMove::CastlingLeft)),
+        castling_board()
+            .parse_san("O-O-O", Color::Black)
+            .map(|mov| mov.kind)
+    );
+    assert_eq!(
+        Ok(Some(PieceType::Knight)),
+        parse("gxh8=N+").map(|mov| mov.promotion)
+    );
+    assert_eq!(
+        Err(ChessError::NoMatchingMove("g8".to_string())),
+        parse("g8")
+    );
+    assert!(matches!(parse("Nz9"), Err(ChessError::BadNotation(_))));
+    assert!(matches!(parse("é4"), Err(ChessError::BadNotation(_))));
+}
+
+#[test]
+fn sliding_attacks() {
+    let dims = Dimensions::STANDARD;
+    let mut occupied: u64 = 0x0123_4567_89AB_CDEF;
+    for _ in 0..200 {
+        occupied = occupied
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        let blockers = (occupied & (occupied >> 7)) as Bitboard;
+        for position in dims.squares() {
+            assert_eq!(
+                bitboard::slide(dims, position, blockers, &bitboard::ROOK_DIRECTIONS),
+                bitboard::rook_attacks(dims, position, blockers)
+            );
+            assert_eq!(
+                bitboard::slide(dims, position, blockers, &bitboard::BISHOP_DIRECTIONS),
+                bitboard::bishop_attacks(dims, position, blockers)
+            );
+        }
+    }
+    assert_eq!(
+        0x0000_0000_0002_0400,
+        bitboard::knight_attacks(dims, sq(0, 0))
+    );
+    // other boards number their squares by their own width
+    let wide = Dimensions::new(10, 8).unwrap();
+    assert_eq!(1 << 12 | 1 << 21, bitboard::knight_attacks(wide, sq(0, 0)));
+    assert_eq!(
+        vec![sq(9, 0), sq(9, 7)],
+        bitboard::squares(wide, bitboard::rook_attacks(wide, sq(9, 1), 0))
+            .filter(|target| target.file() == 9 && target.rank() % 7 == 0)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn zobrist_keys() {
+    fn walk(board: &ChessBoard, color: Color, depth: usize) {
+        assert_eq!(board.full_zobrist_key(color), board.zobrist_key());
+        if depth == 0 {
+            return;
+        }
+        for mov in board.legal_moves(color) {
+            let mut next = board.clone();
+            next.make_move(&mov);
+            walk(&next, color.opposite(), depth - 1);
+        }
+    }
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    walk(&ChessBoard::from_fen(kiwipete).unwrap(), Color::White, 2);
+    let passant = "8/8/8/8/k2Pp2Q/8/8/3K4 b - d3 0 1";
+    walk(&ChessBoard::from_fen(passant).unwrap(), Color::Black, 2);
+
+    let play = |fen: &str, color: Color, moves: &[&str]| {
+        let mut board = ChessBoard::from_fen(fen).unwrap();
+        let mut color = color;
+        for san in moves {
+            let mov = board.parse_san(san, color).unwrap();
+            board.make_move(&mov);
+            color = color.opposite();
+        }
+        board.zobrist_key()
+    };
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    let key = |fen: &str| ChessBoard::from_fen(fen).unwrap().zobrist_key();
+    assert_eq!(
+        key(start),
+        play(start, Color::White, &["Nf3", "Nf6", "Ng1", "Ng8"])
+    );
+    assert_eq!(
+        play(start, Color::White, &["e4", "e5", "Nf3"]),
+        play(start, Color::White, &["Nf3", "e5", "e4"])
+    );
+    // the en passant square only counts when the capture is possible
+    assert_eq!(
+        key("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"),
+        play(start, Color::White, &["e4"])
+    );
+    assert_ne!(
+        key("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"),
+        key("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1")
+    );
+    assert_ne!(key(start), key(&start.replace(" w ", " b ")));
+    assert_ne!(key(start), key(&start.replace("KQkq", "Qkq")));
+}
+
+#[test]
+fn make_and_unmake() {
+    fn walk(board: &mut ChessBoard, color: Color, depth: usize) {
+        if depth == 0 {
+            return;
+        }
+        for mov in board.legal_moves(color) {
+            let before = board.clone();
+            let undo = board.make_move(&mov);
+            walk(board, color.opposite(), depth - 1);
+            board.unmake_move(&mov, undo);
+            assert_eq!(before, *board, "after taking back {}", mov);
+        }
+    }
+    for fen in &[
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    ] {
+        let (mut board, color, _, _) = parse_fen(fen).unwrap();
+        walk(&mut board, color, 3);
+    }
+
+    let mut board = castling_board();
+    let mov = board.parse_san("O-O-O", Color::White).unwrap();
+    let undo = board.make_move(&mov);
+    assert_eq!(
+        Some(PieceType::Rook),
+        board.ref_piece(sq(3, 0)).map(|p| p.piece_type.clone())
+    );
+    board.unmake_move(&mov, undo);
+    assert_eq!(castling_board(), board);
+}
+
+#[test]
+fn attack_queries() {
+    let board = ChessBoard::from_fen("4k3/8/8/b7/8/8/3N4/r3K2r w - - 0 1").unwrap();
+    assert_eq!(vec![sq(0, 0), sq(7, 0)], board.checkers(Color::White));
+    assert_eq!(
+        vec![sq(0, 0), sq(7, 0)],
+        board.attackers_of(sq(4, 0), Color::Black)
+    );
+    assert_eq!(
+        vec![sq(0, 0), sq(7, 0)],
+        board.threats_along(sq(4, 0), Color::White, (1, 0), true)
+    );
+    assert!(board.checkers(Color::Black).is_empty());
+    assert_eq!(
+        vec![Pin {
+            pinned: sq(3, 1),
+            pinner: sq(0, 4),
+            ray: vec![sq(2, 2), sq(1, 3), sq(0, 4)],
+        }],
+        board.pinned_pieces(Color::White)
+    );
+    assert!(board.is_square_defended(sq(3, 1), Color::White));
+    assert!(!board.is_square_defended(sq(2, 2), Color::White));
+    // squares off the board are attacked by nothing
+    assert!(board.attackers_of(sq(10, 10), Color::White).is_empty());
+    assert!(!board.is_square_defended(sq(15, 15), Color::Black));
+    let attacked: Vec<String> = board
+        .attacked_squares(Color::White)
+        .iter()
+        .map(|position| position.to_string())
+        .collect();
+    assert_eq!(
+        vec!["b1", "d1", "f1", "d2", "e2", "f2", "b3", "f3", "c4", "e4"],
+        attacked
+    );
+
+    let mut board: ChessBoard = init_board();
+    board.add_piece(piece_make(Color::White, PieceType::King), sq(4, 0));
+    board.add_piece(piece_make(Color::Black, PieceType::Knight), sq(2, 1));
+    board.add_piece(piece_make(Color::Black, PieceType::Knight), sq(5, 2));
+    assert_eq!(
+        vec![sq(2, 1), sq(5, 2)],
+        board.threats_along(sq(4, 0), Color::White, (1, 2), false)
+    );
+    assert_eq!(vec![sq(2, 1), sq(5, 2)], board.checkers(Color::White));
+    assert!(board.pinned_pieces(Color::White).is_empty());
+}
+
+#[test]
+fn builds_positions() {
+    let mut builder = PositionBuilder::new();
+    builder
+        .place(piece_make(Color::White, PieceType::King), sq(4, 0))
+        .place(piece_make(Color::White, PieceType::Rook), sq(7, 0))
+        .place(piece_make(Color::Black, PieceType::King), sq(4, 7))
+        .place(piece_make(Color::Black, PieceType::Pawn), sq(3, 3))
+        .place(piece_make(Color::White, PieceType::Pawn), sq(4, 3))
+        .castling_rights("K".parse().unwrap())
+        .side_to_move(Color::Black)
+        .en_passant(Some(sq(4, 2)));
+    let (board, color) = builder.build().unwrap();
+    assert_eq!(Color::Black, color);
+    assert_eq!(
+        "4k3/8/8/8/3pP3/8/8/4K2R b K e3 0 1",
+        board.to_fen(color, 0, 1)
+    );
+    assert_eq!(
+        ChessBoard::from_fen("4k3/8/8/8/3pP3/8/8/4K2R b K e3 0 1").unwrap(),
+        board
+    );
+
+    let invalid = |builder: &PositionBuilder| match builder.validate() {
+        Err(ChessError::InvalidPosition(reason)) => reason,
+        other => panic!("expected an invalid position, got {:?}", other),
+    };
+    builder.en_passant(Some(sq(3, 2)));
+    assert_eq!(
+        "no pawn can be captured en passant on d3",
+        invalid(&builder)
+    );
+    builder
+        .en_passant(None)
+        .castling_rights(CastlingRights::all());
+    assert_eq!(
+        "White cannot castle queen side without its king on rank 1 and a rook on that side of it",
+        invalid(&builder)
+    );
+    builder
+        .castling_rights(CastlingRights::default())
+        .place(piece_make(Color::Black, PieceType::Rook), sq(0, 0));
+    assert_eq!("White is in check with Black to move", invalid(&builder));
+    builder
+        .remove(sq(4, 5))
+        .place(piece_make(Color::White, PieceType::Pawn), sq(0, 7));
+    assert_eq!("pawn on the back rank at a8", invalid(&builder));
+    builder
+        .remove(sq(0, 7))
+        .place(piece_make(Color::Black, PieceType::King), sq(0, 7));
+    assert_eq!("Black has 2 kings", invalid(&builder));
+    builder.clear();
+    assert_eq!("White has no king", invalid(&builder));
+    builder.place(piece_make(Color::White, PieceType::Queen), sq(10, 10));
+    assert_eq!("k11 is off the 8x8 board", invalid(&builder));
+
+    let mut start = init_board();
+    start.standard_pieces(Color::White);
+    start.standard_pieces(Color::Black);
+    let builder = PositionBuilder::from_board(&start, Color::White);
+    assert_eq!(Ok((start, Color::White)), builder.build());
+}
+
+#[test]
+fn chess960_setups() {
+    let letters = |index: usize| -> String {
+        chess960_back_rank(index)
+            .unwrap()
+            .iter()
+            .map(|piece_type| piece_type.letter())
+            .collect()
+    };
+    assert_eq!("RNBQKBNR", letters(518));
+    assert_eq!("BBQNNRKR", letters(0));
+    assert_eq!("RKRNNQBB", letters(959));
+    assert_eq!(None, chess960_back_rank(960));
+    let mut setups: Vec<String> = (0..960).map(letters).collect();
+    for setup in &setups {
+        let rooks: Vec<usize> = setup.match_indices('R').map(|(file, _)| file).collect();
+        let king = setup.find('K').unwrap();
+        let bishops: Vec<usize> = setup.match_indices('B').map(|(file, _)| file).collect();
+        assert!(rooks[0] < king && king < rooks[1], "{}", setup);
+        assert_ne!(bishops[0] % 2, bishops[1] % 2, "{}", setup);
     }
+    setups.sort();
+    setups.dedup();
+    assert_eq!(960, setups.len());
+
+    let mut board = init_board();
+    board.chess960_pieces(Color::White, 0).unwrap();
+    board.chess960_pieces(Color::Black, 0).unwrap();
+    assert!(board.is_chess960());
+    assert_eq!(
+        "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1",
+        board.to_fen(Color::White, 0, 1)
+    );
+    assert!(matches!(
+        init_board().chess960_pieces(Color::White, 960),
+        Err(ChessError::InvalidPosition(_))
+    ));
+}
+
+#[test]
+fn chess960_castling() {
+    // the king stays on g1 while the rook jumps from h1 to f1
+    let fen = "4k3/8/8/8/8/8/8/1R4KR w HB - 0 1";
+    let (mut board, ..) = parse_fen(fen).unwrap();
+    assert!(board.is_chess960());
+    assert_eq!(
+        "4k3/8/8/8/8/8/8/1R4KR w KQ - 0 1",
+        board.to_fen(Color::White, 0, 1)
+    );
+    let castling: Vec<Move> = board
+        .get_moves(sq(6, 0))
+        .into_iter()
+        .filter(|mov| mov.kind.is_some())
+        .collect();
+    assert_eq!(
+        vec!["g1h1", "g1b1"],
+        castling.iter().map(Move::to_uci).collect::<Vec<_>>()
+    );
+    let undo = board.make_move(&castling[0]);
+    assert_eq!(
+        "4k3/8/8/8/8/8/8/1R3RK1 b - - 1 1",
+        board.to_fen(Color::Black, 1, 1)
+    );
+    board.unmake_move(&castling[0], undo);
+    let undo = board.make_move(&castling[1]);
+    assert_eq!(
+        "4k3/8/8/8/8/8/8/2KR3R b - - 1 1",
+        board.to_fen(Color::Black, 1, 1)
+    );
+    board.unmake_move(&castling[1], undo);
+    assert_eq!(ChessBoard::from_fen(fen).unwrap(), board);
+
+    // an inner rook is named by its file, and the rook on c1 guards c8
+    let fen = "r3kr1r/8/8/8/8/8/8/2R1K3 b fq - 0 1";
+    let (board, ..) = parse_fen(fen).unwrap();
+    assert_eq!(fen, board.to_fen(Color::Black, 0, 1));
+    assert_eq!(
+        Ok(sq(5, 7)),
+        board.parse_san("O-O", Color::Black).map(|mov| mov.to)
+    );
+    assert_eq!(
+        Err(ChessError::NoMatchingMove("O-O-O".to_string())),
+        board.parse_san("O-O-O", Color::Black)
+    );
+}
+
+#[test]
+fn sized_boards() {
+    let (board, color, ..) = parse_fen("rnqknr/pppppp/6/6/PPPPPP/RNQKNR w - - 0 1").unwrap();
+    assert_eq!(16, board.perft(color, 1));
+    assert_eq!(6, board.ref_board().len());
+
+    let mut builder = PositionBuilder::with_dimensions(Dimensions::new(6, 6).unwrap());
+    builder
+        .place(piece_make(Color::White, PieceType::King), sq(0, 0))
+        .place(piece_make(Color::White, PieceType::Pawn), sq(1, 2))
+        .place(piece_make(Color::White, PieceType::Pawn), sq(4, 4))
+        .place(piece_make(Color::Black, PieceType::King), sq(0, 5))
+        .place(piece_make(Color::Black, PieceType::Pawn), sq(2, 2))
+        .en_passant(Some(sq(2, 3)));
+    let (board, color) = builder.build().unwrap();
+    assert_eq!("k5/4P1/6/1Pp3/6/K5 w - c4 0 1", board.to_fen(color, 0, 1));
+    assert!(board
+        .get_moves(sq(1, 2))
+        .iter()
+        .any(|mov| mov.to == sq(2, 3) && mov.kind == Some(SpecialMove::EnPassant)));
+    // pawns promote on the last rank of the board, wherever that is
+    assert_eq!(4, board.get_moves(sq(4, 4)).len());
+    assert!(builder
+        .place(piece_make(Color::White, PieceType::Pawn), sq(5, 5))
+        .validate()
+        .is_err());
+}
+
+#[test]
+fn fairy_pieces() {
+    let nightrider = PieceDefinition::from_betza("Nightrider", 'x', "NN").unwrap();
+    assert_eq!('X', nightrider.letter());
+    assert_eq!(Reach::Ride, nightrider.movements()[0].reach());
+    assert_eq!(8, nightrider.movements()[0].directions().len());
+    let berolina = PieceDefinition::from_betza("Berolina", 'Y', "mfFcfW").unwrap();
+    assert_eq!(2, berolina.movements()[0].directions().len());
+    assert!(!berolina.movements()[0].captures() && !berolina.movements()[1].moves());
+    for (letter, betza) in [('Y', "RR"), ('Y', "Y"), ('Y', "f"), ('N', "NN"), ('+', "W")] {
+        assert!(matches!(
+            PieceDefinition::from_betza("Broken", letter, betza),
+            Err(ChessError::InvalidPieceDefinition(_))
+        ));
+    }
+
+    let (board, ..) = parse_fen_with_pieces(
+        "4k3/8/8/8/3A4/8/8/4K3 w - - 0 1",
+        &[PieceDefinition::archbishop()],
+    )
+    .unwrap();
+    assert_eq!(21, board.get_moves(sq(3, 3)).len());
+    assert_eq!("A", format!("{}", board.ref_piece(sq(3, 3)).unwrap()));
+    assert!(ChessBoard::from_fen("4k3/8/8/8/3A4/8/8/4K3 w - - 0 1").is_err());
+
+    // the grasshopper needs a piece to hop over and lands right behind it
+    let grasshopper = [PieceDefinition::grasshopper()];
+    let (board, ..) =
+        parse_fen_with_pieces("4k3/8/8/8/3G4/8/3P4/4K3 w - - 0 1", &grasshopper).unwrap();
+    assert_eq!(vec![sq(3, 0)], board.regular_moves(sq(3, 3)));
+    let (board, color, ..) =
+        parse_fen_with_pieces("4k3/8/8/4g3/8/8/4P3/4K3 w - - 0 1", &grasshopper).unwrap();
+    assert_eq!(vec![sq(4, 4)], board.attackers_of(sq(4, 0), Color::Black));
+    assert_eq!(6, board.perft(color, 1));
+
+    let (board, ..) = parse_fen_with_pieces(
+        "4k3/8/8/4c3/8/8/4R3/4K3 w - - 0 1",
+        &[PieceDefinition::chancellor()],
+    )
+    .unwrap();
+    assert_eq!(
+        vec![Pin {
+            pinned: sq(4, 1),
+            pinner: sq(4, 4),
+            ray: vec![sq(4, 2), sq(4, 3), sq(4, 4)],
+        }],
+        board.pinned_pieces(Color::White)
+    );
+    assert_eq!(3, board.get_moves(sq(4, 1)).len());
+
+    let mut board = init_board();
+    let camel = board.define_piece(PieceDefinition::camel()).unwrap();
+    assert_eq!(
+        Ok(camel.clone()),
+        board.define_piece(PieceDefinition::camel())
+    );
+    assert_eq!(Some(camel), board.piece_type_for('l'));
+    assert!(board
+        .define_piece(PieceDefinition::from_betza("Lion", 'L', "KAD").unwrap())
+        .is_err());
 }
@@ -0,0 +1,135 @@
+use super::*;
+
+const KEYED_SQUARES: usize = MAX_BOARD_SIZE * MAX_BOARD_SIZE;
+
+/// Random numbers xored into a position's key for each of its features,
+/// with room for the largest board.
+struct ZobristKeys {
+    pieces: [[[u64; KEYED_SQUARES]; PIECE_KINDS]; 2],
+    castling: [u64; 4],
+    passant_file: [u64; MAX_BOARD_SIZE],
+    black_to_move: u64,
+}
+
+static KEYS: ZobristKeys = ZobristKeys::new();
+
+impl ZobristKeys {
+    const fn new() -> ZobristKeys {
+        let mut keys = ZobristKeys {
+            pieces: [[[0; KEYED_SQUARES]; PIECE_KINDS]; 2],
+            castling: [0; 4],
+            passant_file: [0; MAX_BOARD_SIZE],
+            black_to_move: 0,
+        };
+        let mut state = 0x2F7A_3C5D_1E9B_8046;
+        let mut color = 0;
+        while color < 2 {
+            let mut piece = 0;
+            while piece < PIECE_KINDS {
+                let mut index = 0;
+                while index < KEYED_SQUARES {
+                    state = splitmix(state);
+                    keys.pieces[color][piece][index] = mix(state);
+                    index += 1;
+                }
+                piece += 1;
+            }
+            color += 1;
+        }
+        let mut index = 0;
+        while index < MAX_BOARD_SIZE {
+            state = splitmix(state);
+            keys.passant_file[index] = mix(state);
+            if index < 4 {
+                state = splitmix(state);
+                keys.castling[index] = mix(state);
+            }
+            index += 1;
+        }
+        keys.black_to_move = mix(splitmix(state));
+        keys
+    }
+}
+
+const fn splitmix(state: u64) -> u64 {
+    state.wrapping_add(0x9E37_79B9_7F4A_7C15)
+}
+
+const fn mix(state: u64) -> u64 {
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+pub(super) fn piece_key(piece: &Piece, position: Square) -> u64 {
+    let index = position.rank() * MAX_BOARD_SIZE + position.file();
+    let key = KEYS.pieces[color_index(piece.color)][piece_index(&piece.piece_type)][index];
+    match &piece.piece_type {
+        // custom pieces share their keys, told apart by their letters
+        PieceType::Custom(definition) => mix(key ^ definition.letter() as u64),
+        _ => key,
+    }
+}
+
+pub(super) fn castling_key(rights: CastlingRights) -> u64 {
+    [
+        rights.white_king_side,
+        rights.white_queen_side,
+        rights.black_king_side,
+        rights.black_queen_side,
+    ]
+    .iter()
+    .zip(KEYS.castling.iter())
+    .filter(|(allowed, _)| **allowed)
+    .fold(0, |key, (_, right)| key ^ right)
+}
+
+pub(super) fn passant_key(file: usize) -> u64 {
+    KEYS.passant_file[file]
+}
+
+pub(super) fn side_key() -> u64 {
+    KEYS.black_to_move
+}
+
+impl ChessBoard {
+    /// Identifies the position by piece placement, side to move, castling
+    /// rights and en passant file, the latter only if a capture there is
+    /// legal. Kept up to date move by move.
+    pub fn zobrist_key(&self) -> u64 {
+        self.zobrist
+    }
+
+    /// Computes the key from scratch, which `zobrist_key` always agrees with.
+    pub fn full_zobrist_key(&self, to_move: Color) -> u64 {
+        let mut key = self
+            .dims
+            .squares()
+            .filter_map(|position| {
+                self.ref_piece(position)
+                    .map(|piece| piece_key(piece, position))
+            })
+            .fold(castling_key(self.castling_rights), |key, piece| key ^ piece);
+        if let Some(file) = self.passant_file() {
+            key ^= passant_key(file);
+        }
+        if to_move == Color::Black {
+            key ^= side_key();
+        }
+        key
+    }
+
+    /// The file of the en passant square if capturing there is legal.
+    pub(super) fn passant_file(&self) -> Option<usize> {
+        let (passant_pos, pawn_pos) = self.passant_connection?;
+        let color = self.ref_piece(pawn_pos)?.color.opposite();
+        let capturers = pawn_attacks(self.dims, color.opposite(), passant_pos)
+            & self.bitboards[color_index(color)][piece_index(&PieceType::Pawn)];
+        if squares(self.dims, capturers).any(|from| !self.self_check(from, passant_pos)) {
+            Some(passant_pos.file())
+        } else {
+            None
+        }
+    }
+}
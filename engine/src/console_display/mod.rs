@@ -1,10 +1,10 @@
-use super::piece_logic::*;
+use super::board_logic::Board;
 
-pub fn print_board(board: &[[Option<Piece>; 8]; 8]) {
-    for y in (0..board.len()).rev() {
-        for x in 0..board[0].len() {
+pub fn print_board(board: &Board) {
+    for row in board.iter().rev() {
+        for space in row.iter() {
             print!("|");
-            if let Some(piece) = &board[y][x] {
+            if let Some(piece) = space {
                 print!("{}", piece);
             } else {
                 print!(" ")
@@ -12,6 +12,6 @@ pub fn print_board(board: &[[Option<Piece>; 8]; 8]) {
             print!("|");
         }
         println!();
-        println!("------------------------");
+        println!("{}", "---".repeat(row.len()));
     }
 }
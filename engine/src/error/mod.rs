@@ -0,0 +1,92 @@
+use crate::move_logic::Square;
+use std::{error, fmt};
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum ChessError {
+    NotYourPiece,
+    EmptySquare(Square),
+    IllegalMove {
+        from: Square,
+        to: Square,
+        reason: String,
+    },
+    InvalidPromotion(Square),
+    BadNotation(String),
+    OutOfBounds((isize, isize)),
+    UnsupportedDimensions {
+        files: usize,
+        ranks: usize,
+    },
+    PromotionPending,
+    NoPromotionPending,
+    GameOver,
+    NoDrawToClaim,
+    NothingToUndo,
+    NothingToRedo,
+    NoSuchPly(usize),
+    BadFen(String),
+    InvalidPosition(String),
+    InvalidPieceDefinition(String),
+    UnknownVariant(String),
+    NoMatchingMove(String),
+    AmbiguousMove(String),
+    BadPgn {
+        line: usize,
+        column: usize,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ChessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChessError::NotYourPiece => write!(f, "That is not your piece!"),
+            ChessError::EmptySquare(position) => write!(f, "There is no piece at {}", position),
+            ChessError::IllegalMove { from, to, reason } => write!(
+                f,
+                "Tried to do illegal move! piece at {} cannot move to {}: {}",
+                from, to, reason
+            ),
+            ChessError::InvalidPromotion(position) => write!(
+                f,
+                "Tried to promote unit at wrong place or of wrong type at {}",
+                position
+            ),
+            ChessError::BadNotation(input) => write!(f, "Invalid notation: {:?}", input),
+            ChessError::OutOfBounds(position) => {
+                write!(f, "Tried to access non-existent boardspace {:?}", position)
+            }
+            ChessError::UnsupportedDimensions { files, ranks } => {
+                write!(f, "Cannot play on a board of {}x{} squares", files, ranks)
+            }
+            ChessError::PromotionPending => write!(f, "A pawn is waiting to be promoted"),
+            ChessError::NoPromotionPending => write!(f, "There is no pawn to promote"),
+            ChessError::GameOver => write!(f, "Game is over!"),
+            ChessError::NoDrawToClaim => write!(f, "There is no draw to claim"),
+            ChessError::NothingToUndo => write!(f, "There is no move to take back"),
+            ChessError::NothingToRedo => write!(f, "There is no move to replay"),
+            ChessError::NoSuchPly(ply) => write!(f, "The game has no position after ply {}", ply),
+            ChessError::BadFen(reason) => write!(f, "Invalid FEN: {}", reason),
+            ChessError::InvalidPosition(reason) => write!(f, "Invalid position: {}", reason),
+            ChessError::InvalidPieceDefinition(reason) => {
+                write!(f, "Invalid piece definition: {}", reason)
+            }
+            ChessError::UnknownVariant(name) => write!(f, "Unknown chess variant {:?}", name),
+            ChessError::NoMatchingMove(san) => write!(f, "No legal move matches {:?}", san),
+            ChessError::AmbiguousMove(san) => {
+                write!(f, "{:?} could be more than one legal move", san)
+            }
+            ChessError::BadPgn {
+                line,
+                column,
+                reason,
+            } => write!(
+                f,
+                "Invalid PGN at line {}, column {}: {}",
+                line, column, reason
+            ),
+        }
+    }
+}
+
+impl error::Error for ChessError {}
@@ -1,21 +1,31 @@
 pub mod board_logic;
 pub mod console_display;
+pub mod error;
+pub mod move_logic;
+pub mod pgn;
 pub mod piece_logic;
+pub mod variant;
+
+pub use error::ChessError;
+pub use move_logic::{Dimensions, Move, Square};
+pub use variant::Variant;
+
+use std::convert::TryFrom;
 
 /// Engine for the boardgame "chess"
 ///
 /// Minimal interaction example:
 /// ```
-/// fn example() -> Result<String, String> {
+/// fn example() -> Result<String, maltebl_chess::ChessError> {
 /// #    use maltebl_chess::{chess_game::*, *};
 ///     let mut game = init_standard_chess();
 ///
-///     // construct command from clicked tiles
-///     let origin = (0 as usize, 1 as usize);
-///     let target = (0 as usize, 2 as usize);
-///     let command = format!("{} {}", to_notation(origin)?, to_notation(target)?);
+///     // construct move from clicked tiles
+///     let origin = Square::new(0, 1).unwrap();
+///     let target = Square::new(0, 2).unwrap();
+///     let mov = Move::new(origin, target);
 ///
-///     let msg = game.move_piece(command.clone())?;
+///     let msg = game.play_move(mov.clone())?;
 ///
 ///     game.print_board();
 ///     
@@ -24,103 +34,601 @@ pub mod piece_logic;
 ///         .map(|p| format!("{}", p))
 ///         .unwrap_or(" ".to_owned());
 ///
-///     println!("{}, {}, {}", piece_char, msg, command);
+///     println!("{}, {}, {}", piece_char, msg, mov);
 ///     Ok("".to_owned())
 /// }
 /// example().unwrap();
 /// ````
 pub mod chess_game {
     use super::*;
-    use crate::{board_logic::*, piece_logic::*};
+    use crate::{board_logic::bitboard::color_index, board_logic::*, piece_logic::*, variant::*};
+    use std::{fmt, time::SystemTime};
 
     pub struct ChessGame {
         chess_board: ChessBoard,
-        history: Vec<String>,
         turn: (Color, usize),
+        status: GameStatus,
+        halfmove_clock: usize,
+        /// Checks given by White and Black so far.
+        checks_given: [usize; 2],
+        snapshots: Vec<Snapshot>,
+        ply: usize,
+        automatic_draws: bool,
+        pending_promotion: Option<Move>,
+        default_promotion: Option<PieceType>,
+        variant: Box<dyn Variant>,
+    }
+
+    /// A played move as it appears in the game's history.
+    #[derive(PartialEq, Clone, Debug)]
+    pub struct MoveRecord {
+        pub mov: Move,
+        pub san: String,
+        pub piece: PieceType,
+        pub captured: Option<PieceType>,
+        pub check: bool,
+        pub checkmate: bool,
+        /// Zobrist key of the position the move led to.
+        pub position_hash: u64,
+        pub timestamp: SystemTime,
+    }
+
+    /// The game as it was after a ply, kept so moves can be taken back and replayed.
+    #[derive(Clone)]
+    struct Snapshot {
+        chess_board: ChessBoard,
+        turn: (Color, usize),
+        status: GameStatus,
+        halfmove_clock: usize,
+        checks_given: [usize; 2],
+        position_hash: u64,
+        record: Option<MoveRecord>,
+    }
+
+    #[derive(PartialEq, Copy, Clone, Debug)]
+    pub enum GameStatus {
+        Ongoing,
+        Checkmate {
+            winner: Color,
+        },
+        Stalemate,
+        Draw {
+            reason: DrawReason,
+        },
+        Resignation {
+            winner: Color,
+        },
+        Timeout {
+            winner: Color,
+        },
+        /// Won by a rule of the variant played, e.g. "three checks".
+        VariantWin {
+            winner: Color,
+            reason: &'static str,
+        },
+    }
+
+    #[derive(PartialEq, Copy, Clone, Debug)]
+    pub enum DrawReason {
+        Agreement,
+        FiftyMoveRule,
+        SeventyFiveMoveRule,
+        ThreefoldRepetition,
+        FivefoldRepetition,
+        InsufficientMaterial,
+    }
+
+    impl fmt::Display for DrawReason {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let reason = match self {
+                DrawReason::Agreement => "agreement",
+                DrawReason::FiftyMoveRule => "the fifty-move rule",
+                DrawReason::SeventyFiveMoveRule => "the seventy-five-move rule",
+                DrawReason::ThreefoldRepetition => "threefold repetition",
+                DrawReason::FivefoldRepetition => "fivefold repetition",
+                DrawReason::InsufficientMaterial => "insufficient material",
+            };
+            write!(f, "{}", reason)
+        }
+    }
+
+    impl GameStatus {
+        pub fn is_over(&self) -> bool {
+            *self != GameStatus::Ongoing
+        }
+
+        pub fn winner(&self) -> Option<Color> {
+            match *self {
+                GameStatus::Checkmate { winner }
+                | GameStatus::Resignation { winner }
+                | GameStatus::Timeout { winner }
+                | GameStatus::VariantWin { winner, .. } => Some(winner),
+                _ => None,
+            }
+        }
     }
 
     impl ChessGame {
+        /// A game of `variant` from its start position.
+        pub fn new(variant: impl Variant + 'static) -> Result<ChessGame, ChessError> {
+            let board = variant.start_position()?;
+            Ok(start_game(
+                Box::new(variant),
+                board,
+                (Color::White, 1),
+                0,
+                [0, 0],
+            ))
+        }
+
+        /// A game of `variant` from a FEN position, which may hold the
+        /// variant's custom pieces.
+        pub fn from_variant_fen(
+            variant: impl Variant + 'static,
+            fen: &str,
+        ) -> Result<ChessGame, ChessError> {
+            let (fen, checks_given) = match variant.check_limit() {
+                Some(limit) => split_check_counters(fen, limit)?,
+                None => (fen.to_string(), [0, 0]),
+            };
+            let position = parse_fen_with_pieces(&fen, &variant.pieces())?;
+            Ok(resume_game(Box::new(variant), position, checks_given))
+        }
+
+        pub fn variant(&self) -> &dyn Variant {
+            self.variant.as_ref()
+        }
+
         pub fn get_board(&self) -> Board {
             self.chess_board.get_board()
         }
 
-        pub fn pick_piece(&self, input: String) -> Result<Vec<String>, String> {
-            let piece_position = to_coords(input).expect("Error:");
-            if let Some(piece) = self.chess_board.ref_piece(piece_position) {
+        pub fn chess_board(&self) -> &ChessBoard {
+            &self.chess_board
+        }
+
+        pub fn dimensions(&self) -> Dimensions {
+            self.chess_board.dimensions()
+        }
+
+        pub fn pick_piece(&self, input: String) -> Result<Vec<String>, ChessError> {
+            let mut moves: Vec<String> = self
+                .pick_square(input.parse()?)?
+                .iter()
+                .map(|mov| mov.to.to_string())
+                .collect();
+            moves.dedup();
+            Ok(moves)
+        }
+
+        pub fn status(&self) -> GameStatus {
+            self.status
+        }
+
+        pub fn current_turn(&self) -> Color {
+            self.turn.0
+        }
+
+        pub fn resign(&mut self, color: Color) -> Result<GameStatus, ChessError> {
+            self.end_game(GameStatus::Resignation {
+                winner: color.opposite(),
+            })
+        }
+
+        /// Ends the game in favour of the opponent of `color`, whose clock ran out.
+        pub fn time_out(&mut self, color: Color) -> Result<GameStatus, ChessError> {
+            self.end_game(GameStatus::Timeout {
+                winner: color.opposite(),
+            })
+        }
+
+        pub fn agree_draw(&mut self) -> Result<GameStatus, ChessError> {
+            self.end_game(GameStatus::Draw {
+                reason: DrawReason::Agreement,
+            })
+        }
+
+        /// Starts at 1 and goes up after each move by black.
+        pub fn fullmove_number(&self) -> usize {
+            self.turn.1.div_ceil(2)
+        }
+
+        /// Starts a game from a FEN position, see `board_logic::parse_fen`.
+        pub fn from_fen(fen: &str) -> Result<ChessGame, ChessError> {
+            ChessGame::from_variant_fen(Standard, fen)
+        }
+
+        /// Starts a game from a FEN position holding the custom pieces in
+        /// `pieces`, e.g. Capablanca chess with the archbishop and chancellor.
+        pub fn from_fen_with_pieces(
+            fen: &str,
+            pieces: &[PieceDefinition],
+        ) -> Result<ChessGame, ChessError> {
+            let position = parse_fen_with_pieces(fen, pieces)?;
+            Ok(resume_game(Box::new(Standard), position, [0, 0]))
+        }
+
+        pub fn to_fen(&self) -> String {
+            let fen =
+                self.chess_board
+                    .to_fen(self.turn.0, self.halfmove_clock, self.fullmove_number());
+            self.with_check_counters(fen, self.checks_given)
+        }
+
+        /// The side to move and the fullmove number the game started with.
+        pub fn start_turn(&self) -> (Color, usize) {
+            let start = &self.snapshots[0];
+            (start.turn.0, start.turn.1.div_ceil(2))
+        }
+
+        /// The position the game started from.
+        pub fn start_fen(&self) -> String {
+            let start = &self.snapshots[0];
+            let fen = start.chess_board.to_fen(
+                start.turn.0,
+                start.halfmove_clock,
+                start.turn.1.div_ceil(2),
+            );
+            self.with_check_counters(fen, start.checks_given)
+        }
+
+        /// Adds the checks left to give to FEN positions of variants won by
+        /// checks, see `join_check_counters`.
+        fn with_check_counters(&self, fen: String, checks_given: [usize; 2]) -> String {
+            match self.variant.check_limit() {
+                Some(limit) => join_check_counters(&fen, checks_given, limit),
+                None => fen,
+            }
+        }
+
+        /// How many times `color` has checked the opponent's king.
+        pub fn checks_given(&self, color: Color) -> usize {
+            self.checks_given[color_index(color)]
+        }
+
+        /// Halfmoves since the last capture or pawn move.
+        pub fn halfmove_clock(&self) -> usize {
+            self.halfmove_clock
+        }
+
+        /// How many times the current position has occurred, including now.
+        pub fn repetition_count(&self) -> usize {
+            let current = self.snapshots[self.ply].position_hash;
+            self.snapshots[..=self.ply]
+                .iter()
+                .filter(|snapshot| snapshot.position_hash == current)
+                .count()
+        }
+
+        /// Number of halfmoves played to reach the current position.
+        pub fn ply(&self) -> usize {
+            self.ply
+        }
+
+        /// The moves leading to the current position, oldest first.
+        pub fn history(&self) -> Vec<&MoveRecord> {
+            self.snapshots[1..=self.ply]
+                .iter()
+                .filter_map(|snapshot| snapshot.record.as_ref())
+                .collect()
+        }
+
+        /// Takes back the last move, or a move still waiting for its promotion.
+        pub fn undo(&mut self) -> Result<(), ChessError> {
+            if self.pending_promotion.is_some() {
+                return self.goto_ply(self.ply);
+            }
+            if self.ply == 0 {
+                return Err(ChessError::NothingToUndo);
+            }
+            self.goto_ply(self.ply - 1)
+        }
+
+        /// Replays the move taken back by the last `undo`.
+        pub fn redo(&mut self) -> Result<(), ChessError> {
+            if self.ply + 1 >= self.snapshots.len() {
+                return Err(ChessError::NothingToRedo);
+            }
+            self.goto_ply(self.ply + 1)
+        }
+
+        /// Restores the game as it was after `ply` halfmoves. Moves after it are
+        /// kept for `redo` until a different move is played.
+        pub fn goto_ply(&mut self, ply: usize) -> Result<(), ChessError> {
+            let snapshot = self
+                .snapshots
+                .get(ply)
+                .ok_or(ChessError::NoSuchPly(ply))?
+                .clone();
+            self.chess_board = snapshot.chess_board;
+            self.turn = snapshot.turn;
+            self.status = snapshot.status;
+            self.halfmove_clock = snapshot.halfmove_clock;
+            self.checks_given = snapshot.checks_given;
+            self.pending_promotion = None;
+            self.ply = ply;
+            Ok(())
+        }
+
+        fn snapshot(&self) -> Snapshot {
+            Snapshot {
+                chess_board: self.chess_board.clone(),
+                turn: self.turn,
+                status: self.status,
+                halfmove_clock: self.halfmove_clock,
+                checks_given: self.checks_given,
+                position_hash: self.chess_board.zobrist_key(),
+                record: None,
+            }
+        }
+
+        /// When enabled, threefold repetition and the fifty-move rule end the
+        /// game on their own instead of having to be claimed. Fivefold
+        /// repetition, the seventy-five-move rule and insufficient material
+        /// always end the game.
+        pub fn set_automatic_draws(&mut self, automatic: bool) {
+            self.automatic_draws = automatic;
+        }
+
+        pub fn claimable_draw(&self) -> Option<DrawReason> {
+            if self.status.is_over() {
+                None
+            } else if self.repetition_count() >= 3 {
+                Some(DrawReason::ThreefoldRepetition)
+            } else if self.halfmove_clock >= 100 {
+                Some(DrawReason::FiftyMoveRule)
+            } else {
+                None
+            }
+        }
+
+        pub fn claim_draw(&mut self) -> Result<GameStatus, ChessError> {
+            match self.claimable_draw() {
+                Some(reason) => self.end_game(GameStatus::Draw { reason }),
+                None if self.status.is_over() => Err(ChessError::GameOver),
+                None => Err(ChessError::NoDrawToClaim),
+            }
+        }
+
+        fn end_game(&mut self, status: GameStatus) -> Result<GameStatus, ChessError> {
+            if self.status.is_over() {
+                return Err(ChessError::GameOver);
+            }
+            self.status = status;
+            Ok(status)
+        }
+
+        pub fn pick_square(&self, position: Square) -> Result<Vec<Move>, ChessError> {
+            if self.status.is_over() {
+                return Err(ChessError::GameOver);
+            }
+            if !self.dimensions().contains(position) {
+                return Err(ChessError::OutOfBounds((
+                    position.file() as isize,
+                    position.rank() as isize,
+                )));
+            }
+            if let Some(piece) = self.chess_board.ref_piece(position) {
                 if piece.color != self.turn.0 {
-                    return Err("That's not your piece!".to_string());
+                    return Err(ChessError::NotYourPiece);
                 }
             } else {
-                return Err(format!("There is no piece at {:?}", piece_position));
+                return Err(ChessError::EmptySquare(position));
+            }
+            Ok(self.allowed(self.chess_board.get_moves(position)))
+        }
+
+        /// Every legal move for the side to move.
+        pub fn legal_moves(&self) -> Vec<Move> {
+            self.allowed(self.chess_board.legal_moves(self.turn.0))
+        }
+
+        fn allowed(&self, moves: Vec<Move>) -> Vec<Move> {
+            moves
+                .into_iter()
+                .filter(|mov| self.variant.allows(&self.chess_board, mov))
+                .collect()
+        }
+
+        /// The pieces giving check to the side to move.
+        pub fn checkers(&self) -> Vec<Square> {
+            self.chess_board.checkers(self.turn.0)
+        }
+
+        /// Finds the legal move of the side to move written in standard
+        /// algebraic notation, see `ChessBoard::parse_san`.
+        pub fn parse_san(&self, san: &str) -> Result<Move, ChessError> {
+            self.chess_board.parse_san(san, self.turn.0)
+        }
+
+        /// Plays a move given as two squares, e.g. "g1 f3", in UCI notation,
+        /// e.g. "g1f3", or in standard algebraic notation, e.g. "Nf3".
+        /// Returns the move in standard algebraic notation.
+        pub fn move_piece(&mut self, input: String) -> Result<String, ChessError> {
+            let input = input.trim();
+            let mov = if input.contains(char::is_whitespace) {
+                input.parse()?
+            } else if let Ok(mov) = self.chess_board.parse_uci(input) {
+                mov
+            } else {
+                self.parse_san(input)?
+            };
+            self.play_move(mov)
+        }
+
+        pub fn play_uci(&mut self, uci: &str) -> Result<String, ChessError> {
+            self.play_move(self.chess_board.parse_uci(uci)?)
+        }
+
+        /// The moves leading to the current position in UCI notation, as sent
+        /// to engines after "position startpos moves".
+        pub fn uci_moves(&self) -> Vec<String> {
+            self.history()
+                .iter()
+                .map(|record| record.mov.to_uci())
+                .collect()
+        }
+
+        pub fn play_move(&mut self, mut mov: Move) -> Result<String, ChessError> {
+            if self.status.is_over() {
+                return Err(ChessError::GameOver);
+            }
+            if self.pending_promotion.is_some() {
+                return Err(ChessError::PromotionPending);
+            }
+            let piece = match self.chess_board.ref_piece(mov.from) {
+                Some(piece) if piece.color != self.turn.0 => return Err(ChessError::NotYourPiece),
+                Some(piece) => piece,
+                None => return Err(ChessError::EmptySquare(mov.from)),
+            };
+            let last_rank = self.dimensions().ranks() - 1;
+            let promotes = piece.piece_type == PieceType::Pawn
+                && (mov.to.rank() == 0 || mov.to.rank() == last_rank);
+            if promotes && mov.promotion.is_none() {
+                mov.promotion = self.default_promotion.clone();
+            }
+            let mov = self.chess_board.legal_move(mov)?;
+            if !self.variant.allows(&self.chess_board, &mov) {
+                return Err(ChessError::IllegalMove {
+                    from: mov.from,
+                    to: mov.to,
+                    reason: format!("not allowed in {}", self.variant.name()),
+                });
+            }
+            self.chess_board.make_move(&mov);
+            if promotes && mov.promotion.is_none() {
+                let san = self.snapshots[self.ply].chess_board.san(&mov);
+                self.pending_promotion = Some(mov);
+                return Ok(format!("{} Promotion", san));
+            }
+            Ok(self.finish_move(mov))
+        }
+
+        /// The square of a pawn waiting for `promote`, if any.
+        pub fn pending_promotion(&self) -> Option<Square> {
+            self.pending_promotion.as_ref().map(|mov| mov.to)
+        }
+
+        /// Promote the pawn of a promotion move that was played without a piece.
+        pub fn promote(&mut self, piece_type: PieceType) -> Result<String, ChessError> {
+            let mut mov = self
+                .pending_promotion
+                .take()
+                .ok_or(ChessError::NoPromotionPending)?;
+            if let Err(err) = self.chess_board.promote(mov.to, piece_type.clone()) {
+                self.pending_promotion = Some(mov);
+                return Err(err);
+            }
+            mov.promotion = Some(piece_type);
+            Ok(self.finish_move(mov))
+        }
+
+        /// Pawns promote to this piece when a move doesn't name one; with
+        /// `None` the game waits for `promote` instead.
+        pub fn set_default_promotion(&mut self, piece_type: Option<PieceType>) {
+            self.default_promotion = piece_type;
+        }
+
+        fn finish_move(&mut self, mov: Move) -> String {
+            let before = &self.snapshots[self.ply].chess_board;
+            let piece = before
+                .ref_piece(mov.from)
+                .expect("Moved piece missing from the previous position")
+                .piece_type
+                .clone();
+            let captured = before
+                .captured_piece(&mov)
+                .map(|captured| captured.piece_type.clone());
+            let mut san = before.san(&mov);
+            self.halfmove_clock = if piece == PieceType::Pawn || captured.is_some() {
+                0
+            } else {
+                self.halfmove_clock + 1
+            };
+            self.turn = (self.turn.0.opposite(), 1 + self.turn.1);
+            let check = self.chess_board.is_checked(self.turn.0);
+            if check {
+                self.checks_given[color_index(self.turn.0.opposite())] += 1;
+            }
+            self.snapshots.truncate(self.ply + 1);
+            self.snapshots.push(self.snapshot());
+            self.ply += 1;
+            self.status = self.variant.status(self);
+            let checkmate = matches!(self.status, GameStatus::Checkmate { .. });
+            if checkmate {
+                san.push('#');
+            } else if check {
+                san.push('+');
             }
-            let mut possible_moves: Vec<String> = Vec::new();
-            for (mov, _) in self.chess_board.get_moves(piece_position) {
-                possible_moves.push(to_notation(mov).unwrap());
+            let result = san.clone();
+            let snapshot = &mut self.snapshots[self.ply];
+            snapshot.status = self.status;
+            snapshot.record = Some(MoveRecord {
+                mov,
+                san,
+                piece,
+                captured,
+                check,
+                checkmate,
+                position_hash: snapshot.position_hash,
+                timestamp: SystemTime::now(),
+            });
+            match self.status {
+                GameStatus::Checkmate { .. } => "Game is over! It's a checkmate!".to_string(),
+                GameStatus::Stalemate => "Game is over! It's a stalemate!".to_string(),
+                GameStatus::Draw { reason } => format!("Game is over! It's a draw by {}!", reason),
+                GameStatus::VariantWin { winner, reason } => {
+                    format!("Game is over! {:?} wins by {}!", winner, reason)
+                }
+                _ => result,
             }
-            Ok(possible_moves)
         }
 
-        pub fn move_piece(&mut self, input: String) -> Result<String, String> {
-            if input.len() == 5 {
-                let mut input = input.split_whitespace();
-                let move_from = to_coords(input.next().unwrap().to_string())?;
-                let move_to = to_coords(input.next().unwrap().to_string())?;
-                if let Some(piece) = self.chess_board.ref_piece(move_from) {
-                    if piece.color != self.turn.0 {
-                        return Err("That is not your piece!".to_string());
+        /// How the game stands by the standard rules: checkmate, stalemate
+        /// or a draw that ends the game, see `Variant::status`.
+        pub fn standard_status(&self) -> GameStatus {
+            let color = self.turn.0;
+            if self.legal_moves().is_empty() {
+                return if self.chess_board.is_checked(color) {
+                    GameStatus::Checkmate {
+                        winner: color.opposite(),
                     }
                 } else {
-                    return Err(format!("There is no piece at {:?}", move_from));
-                }
-                let mut result = self.chess_board.move_piece(move_from, move_to);
-                if result.is_ok() {
-                    let mov = result.clone().unwrap();
-                    let mov = mov.trim();
-                    let mut history = format!("{}. ", self.turn.1);
-                    history.push_str(mov);
-                    self.history.push(history);
-                    self.turn = (
-                        if self.turn.0 == Color::White {
-                            Color::Black
-                        } else {
-                            Color::White
-                        },
-                        1 + self.turn.1,
-                    );
-                    if self.chess_board.is_checked(self.turn.0) {
-                        result = Ok(result.unwrap() + " Check!");
-                    }
-                    if self.chess_board.is_checkmate(self.turn.0) {
-                        return Ok("Game is over! It's a checkmate!".to_string());
-                    }
-                }
-                result
+                    GameStatus::Stalemate
+                };
+            }
+            let reason = if self.repetition_count() >= 5 {
+                Some(DrawReason::FivefoldRepetition)
+            } else if self.halfmove_clock >= 150 {
+                Some(DrawReason::SeventyFiveMoveRule)
+            } else if self.variant.insufficient_material(&self.chess_board) {
+                Some(DrawReason::InsufficientMaterial)
+            } else if self.automatic_draws {
+                self.claimable_draw()
             } else {
-                Err("Error: enter move as e.g:a4 a3".to_string())
-            }
-        }
-
-        pub fn promotion(&mut self, input: String) -> Result<String, String> {
-            if input.len() == 3 {
-                let mut chars = input.chars();
-                let position = to_coords(format!(
-                    "{}{}",
-                    chars.next().unwrap(),
-                    chars.next().unwrap()
-                ))?;
-                let piece_type = match chars.next() {
-                    Some('Q') => PieceType::Queen,
-                    Some('B') => PieceType::Bishop,
-                    Some('N') => PieceType::Knight,
-                    Some('R') => PieceType::Rook,
-                    _ => PieceType::Pawn,
+                None
+            };
+            match reason {
+                Some(reason) => GameStatus::Draw { reason },
+                None => GameStatus::Ongoing,
+            }
+        }
+
+        pub fn promotion(&mut self, input: String) -> Result<String, ChessError> {
+            let chars: Vec<char> = input.chars().collect();
+            if chars.len() == 3 || chars.len() == 4 {
+                let (letter, square) = chars.split_last().unwrap();
+                let position = square.iter().collect::<String>().parse()?;
+                let piece_type = match self.chess_board.piece_type_for(*letter) {
+                    Some(piece_type) if letter.is_ascii_uppercase() => piece_type,
+                    _ => return Err(ChessError::BadNotation(input)),
                 };
-                if piece_type == PieceType::Pawn {
-                    return Err("Must provide promotion input as e.g:a8Q".to_string());
+                if self.pending_promotion() != Some(position) {
+                    return Err(ChessError::InvalidPromotion(position));
                 }
-                self.chess_board.promote(position, piece_type)
+                self.promote(piece_type)
             } else {
-                Err("Must provide promotion input as e.g:a8Q".to_string())
+                Err(ChessError::BadNotation(input))
             }
         }
         pub fn print_board(&self) {
@@ -128,40 +636,68 @@ pub mod chess_game {
         }
     }
     pub fn init_standard_chess() -> ChessGame {
-        let mut board = init_board();
-        board.standard_pieces(Color::White);
-        board.standard_pieces(Color::Black);
-        ChessGame {
-            chess_board: board,
-            history: Vec::new(),
-            turn: (Color::White, 1),
-        }
+        ChessGame::new(Standard).expect("Standard start position is invalid")
     }
-}
 
-pub fn to_coords(input: String) -> Result<(usize, usize), String> {
-    if input.len() == 2 {
-        let mut input = input.chars();
-        let mut pos_x = input.next().unwrap() as isize - 96;
-        let mut pos_y: isize = input.next().unwrap().to_string().parse().unwrap();
-        pos_x -= 1;
-        pos_y -= 1;
-        if pos_x < 0 || pos_x > 7 || pos_y < 0 || pos_y > 7 {
-            println!("{}{}", pos_x, pos_y);
-            return Err(String::from("tried to access non-existent boardspace"));
-        }
-        Ok((pos_x as usize, pos_y as usize))
-    } else {
-        Err(String::from(
-            "invalid notation, cannot find coords on board",
-        ))
+    /// A Chess960 game from start position `index`, 0 to 959, see
+    /// `chess960_back_rank`.
+    pub fn init_chess960(index: usize) -> Result<ChessGame, ChessError> {
+        ChessGame::new(Chess960(index))
     }
-}
 
-pub fn to_notation(position: (usize, usize)) -> Result<String, String> {
-    let (x, y) = position;
-    if x > 8 || y > 8 {
-        return Err(String::from("tried to access non-existent boardspace"));
+    /// A game from a position read by `parse_fen`, with the checks each
+    /// side has given.
+    fn resume_game(
+        variant: Box<dyn Variant>,
+        (board, to_move, halfmove_clock, fullmove_number): (ChessBoard, Color, usize, usize),
+        checks_given: [usize; 2],
+    ) -> ChessGame {
+        let turn = if to_move == Color::White {
+            2 * fullmove_number - 1
+        } else {
+            2 * fullmove_number
+        };
+        start_game(
+            variant,
+            board,
+            (to_move, turn),
+            halfmove_clock,
+            checks_given,
+        )
+    }
+
+    fn start_game(
+        variant: Box<dyn Variant>,
+        mut chess_board: ChessBoard,
+        turn: (Color, usize),
+        halfmove_clock: usize,
+        checks_given: [usize; 2],
+    ) -> ChessGame {
+        chess_board.set_promotion_choices(variant.promotion_choices());
+        let mut game = ChessGame {
+            chess_board,
+            turn,
+            status: GameStatus::Ongoing,
+            halfmove_clock,
+            checks_given,
+            snapshots: Vec::new(),
+            ply: 0,
+            automatic_draws: false,
+            pending_promotion: None,
+            default_promotion: None,
+            variant,
+        };
+        game.snapshots.push(game.snapshot());
+        game.status = game.variant.status(&game);
+        game.snapshots[0].status = game.status;
+        game
     }
-    Ok(format!("{}{}", (x + 97) as u8 as char, y + 1))
+}
+
+pub fn to_coords(input: String) -> Result<(usize, usize), ChessError> {
+    Ok(input.parse::<Square>()?.into())
+}
+
+pub fn to_notation(position: (usize, usize)) -> Result<String, ChessError> {
+    Ok(Square::try_from(position)?.to_string())
 }
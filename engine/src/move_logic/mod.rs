@@ -0,0 +1,264 @@
+use super::piece_logic::*;
+use crate::ChessError;
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+/// Files and ranks of the standard board.
+pub const BOARD_SIZE: usize = 8;
+/// The most files or ranks a board can have, a to p and 1 to 16.
+pub const MAX_BOARD_SIZE: usize = 16;
+
+#[cfg(test)]
+mod tests;
+
+/// A space on a board of at most `MAX_BOARD_SIZE` files and ranks. Whether
+/// it lies on a particular board is up to that board's `Dimensions`.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
+pub struct Square {
+    file: usize,
+    rank: usize,
+}
+
+impl Square {
+    pub fn new(file: usize, rank: usize) -> Option<Square> {
+        if file < MAX_BOARD_SIZE && rank < MAX_BOARD_SIZE {
+            Some(Square { file, rank })
+        } else {
+            None
+        }
+    }
+
+    pub fn file(self) -> usize {
+        self.file
+    }
+
+    pub fn rank(self) -> usize {
+        self.rank
+    }
+
+    pub fn coords(self) -> (usize, usize) {
+        (self.file, self.rank)
+    }
+
+    /// The square `(x, y)` steps away, or `None` if that falls off the
+    /// largest board. See `Dimensions::offset` for a particular board.
+    pub fn offset(self, (x, y): (isize, isize)) -> Option<Square> {
+        let file = self.file as isize + x;
+        let rank = self.rank as isize + y;
+        if file < 0 || rank < 0 {
+            return None;
+        }
+        Square::new(file as usize, rank as usize)
+    }
+}
+
+/// The width and height of a board, e.g. 6x6 for Los Alamos chess or 10x8
+/// for Capablanca chess.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct Dimensions {
+    files: usize,
+    ranks: usize,
+}
+
+impl Dimensions {
+    pub const STANDARD: Dimensions = Dimensions {
+        files: BOARD_SIZE,
+        ranks: BOARD_SIZE,
+    };
+
+    /// Boards need 4 to 16 files and ranks, and no more than 128 squares so
+    /// that a bitboard still covers them.
+    pub fn new(files: usize, ranks: usize) -> Result<Dimensions, ChessError> {
+        let sizes = 4..=MAX_BOARD_SIZE;
+        if sizes.contains(&files) && sizes.contains(&ranks) && files * ranks <= 128 {
+            Ok(Dimensions { files, ranks })
+        } else {
+            Err(ChessError::UnsupportedDimensions { files, ranks })
+        }
+    }
+
+    pub fn files(self) -> usize {
+        self.files
+    }
+
+    pub fn ranks(self) -> usize {
+        self.ranks
+    }
+
+    pub fn contains(self, square: Square) -> bool {
+        square.file < self.files && square.rank < self.ranks
+    }
+
+    /// The square `(x, y)` steps away from `square`, or `None` if that falls
+    /// off this board.
+    pub fn offset(self, square: Square, direction: (isize, isize)) -> Option<Square> {
+        square
+            .offset(direction)
+            .filter(|target| self.contains(*target))
+    }
+
+    /// Every square, rank by rank starting at a1.
+    pub fn squares(self) -> impl Iterator<Item = Square> {
+        (0..self.ranks).flat_map(move |rank| (0..self.files).map(move |file| Square { file, rank }))
+    }
+}
+
+impl Default for Dimensions {
+    fn default() -> Dimensions {
+        Dimensions::STANDARD
+    }
+}
+
+impl fmt::Display for Dimensions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.files, self.ranks)
+    }
+}
+
+/// Splits a square's name, a letter and a rank number, e.g. "a10", off the
+/// front of `input`.
+fn split_square(input: &str) -> Option<(&str, &str)> {
+    let mut chars = input.char_indices().skip(1);
+    let end = chars
+        .find(|(_, c)| !c.is_ascii_digit())
+        .map_or(input.len(), |(index, _)| index);
+    if end < 2 {
+        return None;
+    }
+    Some(input.split_at(end))
+}
+
+impl From<Square> for (usize, usize) {
+    fn from(square: Square) -> (usize, usize) {
+        square.coords()
+    }
+}
+
+impl TryFrom<(usize, usize)> for Square {
+    type Error = ChessError;
+
+    fn try_from((file, rank): (usize, usize)) -> Result<Square, ChessError> {
+        Square::new(file, rank).ok_or(ChessError::OutOfBounds((file as isize, rank as isize)))
+    }
+}
+
+impl FromStr for Square {
+    type Err = ChessError;
+
+    fn from_str(input: &str) -> Result<Square, ChessError> {
+        let bad_notation = || ChessError::BadNotation(input.to_string());
+        let mut chars = input.chars();
+        let file = chars.next().ok_or_else(bad_notation)?;
+        let rank: usize = chars.as_str().parse().map_err(|_| bad_notation())?;
+        if !file.is_ascii_lowercase()
+            || rank == 0
+            || !chars.as_str().starts_with(|c: char| c.is_ascii_digit())
+        {
+            return Err(bad_notation());
+        }
+        let file = file as usize - 'a' as usize;
+        Square::try_from((file, rank - 1))
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", (self.file as u8 + b'a') as char, self.rank + 1)
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum SpecialMove {
+    Pawn2Step,
+    CastlingLeft,
+    CastlingRight,
+    EnPassant,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Move {
+    pub from: Square,
+    pub to: Square,
+    pub promotion: Option<PieceType>,
+    pub kind: Option<SpecialMove>,
+}
+
+impl Move {
+    pub fn new(from: Square, to: Square) -> Move {
+        Move {
+            from,
+            to,
+            promotion: None,
+            kind: None,
+        }
+    }
+
+    /// Reads a move in UCI long algebraic notation, e.g. "e2e4", "e7e8q",
+    /// "e1g1" for castling or "a9a10" on a larger board.
+    pub fn from_uci(input: &str) -> Result<Move, ChessError> {
+        let bad_notation = || ChessError::BadNotation(input.to_string());
+        if !input.is_ascii() {
+            return Err(bad_notation());
+        }
+        let (from, rest) = split_square(input).ok_or_else(bad_notation)?;
+        let (to, promotion) = split_square(rest).ok_or_else(bad_notation)?;
+        if promotion.len() > 1 {
+            return Err(bad_notation());
+        }
+        let mut mov = Move::new(
+            from.parse().map_err(|_| bad_notation())?,
+            to.parse().map_err(|_| bad_notation())?,
+        );
+        if let Some(letter) = promotion.chars().next() {
+            mov.promotion = match letter {
+                'q' | 'r' | 'b' | 'n' => PieceType::from_letter(letter),
+                _ => return Err(bad_notation()),
+            };
+        }
+        Ok(mov)
+    }
+
+    pub fn to_uci(&self) -> String {
+        let mut uci = format!("{}{}", self.from, self.to);
+        if let Some(piece_type) = &self.promotion {
+            uci.push(piece_type.letter().to_ascii_lowercase());
+        }
+        uci
+    }
+}
+
+/// Moves are written as the two squares separated by a space, e.g. "e2 e4",
+/// with the promotion piece after the target square, e.g. "e7 e8q".
+impl FromStr for Move {
+    type Err = ChessError;
+
+    fn from_str(input: &str) -> Result<Move, ChessError> {
+        let bad_notation = || ChessError::BadNotation(input.to_string());
+        let mut squares = input.split_whitespace();
+        match (squares.next(), squares.next(), squares.next()) {
+            (Some(from), Some(to), None) => {
+                let (to, promotion) = split_square(to).ok_or_else(bad_notation)?;
+                let mut mov = Move::new(from.parse()?, to.parse()?);
+                let promotion = promotion.trim_start_matches('=');
+                if !promotion.is_empty() {
+                    let mut letters = promotion.chars();
+                    mov.promotion = letters.next().and_then(PieceType::from_letter);
+                    if mov.promotion.is_none() || letters.next().is_some() {
+                        return Err(bad_notation());
+                    }
+                }
+                Ok(mov)
+            }
+            _ => Err(bad_notation()),
+        }
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.from, self.to)?;
+        if let Some(piece_type) = &self.promotion {
+            write!(f, "{}", piece_type.letter().to_ascii_lowercase())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use super::*;
+
+#[test]
+fn parses_squares() {
+    let square: Square = "e4".parse().unwrap();
+    assert_eq!((4, 3), square.coords());
+    assert_eq!("e4", square.to_string());
+    assert!("z9".parse::<Square>().is_err());
+    assert!("é4".parse::<Square>().is_err());
+    assert!("a0".parse::<Square>().is_err());
+    assert!("a".parse::<Square>().is_err());
+    assert!("a+1".parse::<Square>().is_err());
+    assert_eq!("j10", "j10".parse::<Square>().unwrap().to_string());
+    assert!("q1".parse::<Square>().is_err());
+}
+
+#[test]
+fn offsets_stay_on_board() {
+    let dims = Dimensions::STANDARD;
+    let square = Square::new(0, 7).unwrap();
+    assert_eq!(Square::new(1, 6), dims.offset(square, (1, -1)));
+    assert_eq!(None, dims.offset(square, (-1, 0)));
+    assert_eq!(None, dims.offset(square, (0, 1)));
+    assert_eq!(Square::new(0, 8), square.offset((0, 1)));
+    assert_eq!(64, dims.squares().count());
+    let los_alamos = Dimensions::new(6, 6).unwrap();
+    assert_eq!(36, los_alamos.squares().count());
+    assert!(!los_alamos.contains(Square::new(6, 0).unwrap()));
+    assert_eq!("6x6", los_alamos.to_string());
+    assert_eq!(
+        Err(ChessError::UnsupportedDimensions {
+            files: 12,
+            ranks: 12
+        }),
+        Dimensions::new(12, 12)
+    );
+    assert!(Dimensions::new(3, 8).is_err());
+}
+
+#[test]
+fn parses_moves() {
+    let mov: Move = "a2 a4".parse().unwrap();
+    assert_eq!(Move::new("a2".parse().unwrap(), "a4".parse().unwrap()), mov);
+    assert_eq!("a2 a4", mov.to_string());
+    assert!("a2a4".parse::<Move>().is_err());
+    assert!("a2 a4 a5".parse::<Move>().is_err());
+}
+
+#[test]
+fn parses_uci() {
+    let mov = Move::from_uci("e7e8q").unwrap();
+    assert_eq!("e7", mov.from.to_string());
+    assert_eq!(Some(PieceType::Queen), mov.promotion);
+    assert_eq!("e7e8q", mov.to_uci());
+    assert_eq!("e1g1", Move::from_uci("e1g1").unwrap().to_uci());
+    let mov = Move::from_uci("a9a10q").unwrap();
+    assert_eq!((0, 9), mov.to.coords());
+    assert_eq!("a9a10q", mov.to_uci());
+    assert_eq!(Ok(mov), "a9 a10=Q".parse());
+    for bad in &["e2e", "z9e4", "é4e5", "e2e4k", "e7e8Q", "e2e4qq", "e2 e4"] {
+        assert_eq!(
+            Err(ChessError::BadNotation(bad.to_string())),
+            Move::from_uci(bad)
+        );
+    }
+}
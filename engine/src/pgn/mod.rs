@@ -0,0 +1,389 @@
+use crate::chess_game::*;
+use crate::piece_logic::Color;
+use crate::variant::*;
+use crate::ChessError;
+
+#[cfg(test)]
+mod tests;
+
+pub const STANDARD_START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// The tags every PGN game carries, in order, with their value when unknown.
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+/// A game read from PGN, replayed through the engine's rules.
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub game: ChessGame,
+    /// The game termination marker, e.g. "1-0" or "*".
+    pub result: String,
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// "1-0", "0-1", "1/2-1/2" or "*" for a game still in progress.
+pub fn result_marker(status: GameStatus) -> &'static str {
+    match status {
+        GameStatus::Ongoing => "*",
+        GameStatus::Stalemate | GameStatus::Draw { .. } => "1/2-1/2",
+        _ if status.winner() == Some(Color::White) => "1-0",
+        _ => "0-1",
+    }
+}
+
+/// Writes `game` as PGN with the seven tag roster first, followed by any
+/// other `tags`. Tags not given are filled in with their unknown value and
+/// the result is taken from the game unless a "Result" tag is given.
+pub fn write_pgn(game: &ChessGame, tags: &[(&str, &str)]) -> String {
+    let given = |name: &str| {
+        tags.iter()
+            .find(|(tag, _)| *tag == name)
+            .map(|(_, value)| *value)
+    };
+    let result = given("Result").unwrap_or_else(|| result_marker(game.status()));
+    let mut pgn = String::new();
+    for (name, unknown) in SEVEN_TAG_ROSTER.iter() {
+        let value = match *name {
+            "Result" => result,
+            _ => given(name).unwrap_or(unknown),
+        };
+        pgn.push_str(&tag_pair(name, value));
+    }
+    let variant = game.variant().name();
+    if variant != Standard.name() {
+        pgn.push_str(&tag_pair("Variant", variant));
+    }
+    // without a FEN tag, readers start from the variant's own position
+    let start = game.start_fen();
+    let variant_start = variant_by_name(variant)
+        .and_then(|variant| ChessGame::new(variant).ok())
+        .map(|game| game.start_fen());
+    if variant_start.as_deref() != Some(start.as_str()) {
+        pgn.push_str(&tag_pair("SetUp", "1"));
+        pgn.push_str(&tag_pair("FEN", &start));
+    }
+    for (name, value) in tags {
+        let is_roster = SEVEN_TAG_ROSTER.iter().any(|(roster, _)| roster == name);
+        if !is_roster && !["Variant", "SetUp", "FEN"].contains(name) {
+            pgn.push_str(&tag_pair(name, value));
+        }
+    }
+    pgn.push('\n');
+
+    let (mut color, mut number) = game.start_turn();
+    let mut tokens: Vec<String> = Vec::new();
+    for record in game.history() {
+        if color == Color::White {
+            tokens.push(format!("{}.", number));
+        } else if tokens.is_empty() {
+            tokens.push(format!("{}...", number));
+        }
+        tokens.push(record.san.clone());
+        if color == Color::Black {
+            number += 1;
+        }
+        color = color.opposite();
+    }
+    tokens.push(result.to_string());
+
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.len() + 1 + token.len() > 79 {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&token);
+    }
+    pgn.push_str(&line);
+    pgn.push('\n');
+    pgn
+}
+
+fn tag_pair(name: &str, value: &str) -> String {
+    format!(
+        "[{} \"{}\"]\n",
+        name,
+        value.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+/// Reads every game in `input`, skipping comments, NAGs and variations and
+/// replaying the main line of each through the engine. Fails on the first
+/// malformed or illegal move, giving its line and column.
+pub fn read_pgn(input: &str) -> Result<Vec<PgnGame>, ChessError> {
+    let mut lexer = Lexer::new(input);
+    let mut games = Vec::new();
+    let mut tags: Vec<(String, String)> = Vec::new();
+    let mut game: Option<ChessGame> = None;
+    while let Some((token, line, column)) = lexer.next_token()? {
+        let at = |err: ChessError| ChessError::BadPgn {
+            line,
+            column,
+            reason: err.to_string(),
+        };
+        match token {
+            Token::Tag(name, value) => {
+                if let Some(game) = game.take() {
+                    // the previous game ended without a termination marker
+                    games.push(PgnGame {
+                        tags: std::mem::take(&mut tags),
+                        game,
+                        result: "*".to_string(),
+                    });
+                }
+                tags.push((name, value));
+            }
+            Token::San(san) => {
+                if game.is_none() {
+                    game = Some(initial_game(&tags).map_err(at)?);
+                }
+                let game = game.as_mut().unwrap();
+                game.parse_san(&san)
+                    .and_then(|mov| game.play_move(mov))
+                    .map_err(|err| ChessError::BadPgn {
+                        line,
+                        column,
+                        reason: format!("illegal move {}: {}", san, err),
+                    })?;
+            }
+            Token::Result(result) => {
+                let game = match game.take() {
+                    Some(game) => game,
+                    None => initial_game(&tags).map_err(at)?,
+                };
+                games.push(PgnGame {
+                    tags: std::mem::take(&mut tags),
+                    game,
+                    result,
+                });
+            }
+        }
+    }
+    if game.is_some() || !tags.is_empty() {
+        let game = match game {
+            Some(game) => game,
+            None => initial_game(&tags)?,
+        };
+        games.push(PgnGame {
+            tags,
+            game,
+            result: "*".to_string(),
+        });
+    }
+    Ok(games)
+}
+
+/// The game the tags start from, in the variant named by the "Variant" tag.
+fn initial_game(tags: &[(String, String)]) -> Result<ChessGame, ChessError> {
+    let tag = |tag: &str| {
+        tags.iter()
+            .find(|(name, _)| name == tag)
+            .map(|(_, value)| value.as_str())
+    };
+    let variant: Box<dyn Variant> = match tag("Variant") {
+        Some(name) => {
+            variant_by_name(name).ok_or_else(|| ChessError::UnknownVariant(name.to_string()))?
+        }
+        None => Box::new(Standard),
+    };
+    match tag("FEN") {
+        Some(fen) => ChessGame::from_variant_fen(variant, fen),
+        None => ChessGame::new(variant),
+    }
+}
+
+enum Token {
+    Tag(String, String),
+    San(String),
+    Result(String),
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Lexer<'a> {
+        Lexer {
+            chars: input.chars().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let next = self.chars.next()?;
+        if next == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(next)
+    }
+
+    fn error(line: usize, column: usize, reason: &str) -> ChessError {
+        ChessError::BadPgn {
+            line,
+            column,
+            reason: reason.to_string(),
+        }
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(next) = self.next_char() {
+            if next == '\n' {
+                break;
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|next| next.is_whitespace()) {
+            self.next_char();
+        }
+    }
+
+    /// The next tag pair, move or termination marker with the line and column
+    /// it starts at, or `None` at the end of the input.
+    fn next_token(&mut self) -> Result<Option<(Token, usize, usize)>, ChessError> {
+        let mut variations: Vec<(usize, usize)> = Vec::new();
+        loop {
+            let (line, column) = (self.line, self.column);
+            let next = match self.chars.peek() {
+                Some(next) => *next,
+                None => {
+                    return match variations.pop() {
+                        Some((line, column)) => {
+                            Err(Lexer::error(line, column, "variation is never closed"))
+                        }
+                        None => Ok(None),
+                    }
+                }
+            };
+            match next {
+                _ if next.is_whitespace() => {
+                    self.next_char();
+                }
+                '%' if column == 1 => self.skip_line(),
+                ';' => self.skip_line(),
+                '{' => loop {
+                    match self.next_char() {
+                        Some('}') => break,
+                        Some(_) => (),
+                        None => return Err(Lexer::error(line, column, "comment is never closed")),
+                    }
+                },
+                '(' => {
+                    self.next_char();
+                    variations.push((line, column));
+                }
+                ')' => {
+                    self.next_char();
+                    if variations.pop().is_none() {
+                        return Err(Lexer::error(line, column, "no variation to close"));
+                    }
+                }
+                '$' => {
+                    self.next_char();
+                    while self.chars.peek().is_some_and(|next| next.is_ascii_digit()) {
+                        self.next_char();
+                    }
+                }
+                '[' if variations.is_empty() => {
+                    let tag = self.tag(line, column)?;
+                    return Ok(Some((tag, line, column)));
+                }
+                _ => {
+                    let mut symbol = String::new();
+                    while let Some(&next) = self.chars.peek() {
+                        if next.is_whitespace() || "[]{}();$".contains(next) {
+                            break;
+                        }
+                        symbol.push(next);
+                        self.next_char();
+                    }
+                    if symbol.is_empty() {
+                        self.next_char();
+                        return Err(Lexer::error(
+                            line,
+                            column,
+                            &format!("unexpected '{}'", next),
+                        ));
+                    }
+                    if !variations.is_empty() {
+                        continue;
+                    }
+                    if let "1-0" | "0-1" | "1/2-1/2" | "*" = symbol.as_str() {
+                        return Ok(Some((Token::Result(symbol), line, column)));
+                    }
+                    // move numbers like "12." or "12..." may be written against the move
+                    let number = symbol.trim_start_matches(|c: char| c.is_ascii_digit());
+                    let san = if number.starts_with('.') {
+                        number.trim_start_matches('.')
+                    } else {
+                        symbol.as_str()
+                    };
+                    let san_column = column + symbol.len() - san.len();
+                    if san.is_empty() || san.chars().all(|c| c == '!' || c == '?') {
+                        continue;
+                    }
+                    return Ok(Some((Token::San(san.to_string()), line, san_column)));
+                }
+            }
+        }
+    }
+
+    fn tag(&mut self, line: usize, column: usize) -> Result<Token, ChessError> {
+        let bad_tag = || Lexer::error(line, column, "malformed tag pair");
+        self.next_char();
+        self.skip_whitespace();
+        let mut name = String::new();
+        while let Some(&next) = self.chars.peek() {
+            if !next.is_ascii_alphanumeric() && next != '_' {
+                break;
+            }
+            name.push(next);
+            self.next_char();
+        }
+        self.skip_whitespace();
+        if name.is_empty() || self.next_char() != Some('"') {
+            return Err(bad_tag());
+        }
+        let mut value = String::new();
+        loop {
+            match self.next_char() {
+                Some('"') => break,
+                Some('\\') => value.push(self.next_char().ok_or_else(bad_tag)?),
+                Some('\n') | None => return Err(bad_tag()),
+                Some(next) => value.push(next),
+            }
+        }
+        self.skip_whitespace();
+        if self.next_char() != Some(']') {
+            return Err(bad_tag());
+        }
+        Ok(Token::Tag(name, value))
+    }
+}
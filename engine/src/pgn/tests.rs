@@ -0,0 +1,164 @@
+use crate::{pgn::*, piece_logic::PieceDefinition};
+
+const OPERA_GAME: &str = r#"[Event "Paris"]
+[Site "Paris FRA"]
+[Date "1858.??.??"]
+[Round "?"]
+[White "Paul Morphy"]
+[Black "Duke Karl / Count Isouard"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 d6 3. d4 Bg4 {This is a weak move already.} 4. dxe5 Bxf3 5.
+Qxf3 dxe5 6. Bc4 Nf6 7. Qb3 $1 Qe7 8. Nc3 (8. Qxb7 Qb4+ 9. Qxb4 Bxb4+) 8... c6
+9. Bg5 b5 10. Nxb5! cxb5 11. Bxb5+ Nbd7 12. O-O-O Rd8 13. Rxd7 Rxd7 14. Rd1 Qe6
+; Black is helpless
+15. Bxd7+ Nxd7 16. Qb8+ Nxb8 17. Rd8# 1-0
+"#;
+
+#[test]
+fn reads_games() {
+    let input = format!(
+        "{}\n[Event \"Second\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 30\"]\n\n30... Kd7 31.e4 *\n",
+        OPERA_GAME
+    );
+    let games = read_pgn(&input).unwrap();
+    assert_eq!(2, games.len());
+
+    let opera = &games[0];
+    assert_eq!(Some("Paul Morphy"), opera.tag("White"));
+    assert_eq!("1-0", opera.result);
+    assert_eq!(33, opera.game.history().len());
+    assert_eq!(
+        GameStatus::Checkmate {
+            winner: Color::White
+        },
+        opera.game.status()
+    );
+
+    let second = &games[1];
+    assert_eq!("*", second.result);
+    assert_eq!(2, second.game.history().len());
+    assert_eq!("8/3k4/8/8/4P3/8/8/4K3 b - e3 0 31", second.game.to_fen());
+}
+
+#[test]
+fn writes_games() {
+    let game = read_pgn(OPERA_GAME).unwrap().remove(0).game;
+    let pgn = write_pgn(
+        &game,
+        &[
+            ("White", "Paul Morphy"),
+            ("Opening", "Philidor \"Defence\""),
+        ],
+    );
+    assert!(pgn.starts_with(
+        "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"Paul Morphy\"]\n[Black \"?\"]\n[Result \"1-0\"]\n[Opening \"Philidor \\\"Defence\\\"\"]\n\n1. e4 e5 2. Nf3 d6"
+    ));
+    assert!(pgn.ends_with("16. Qb8+ Nxb8 17. Rd8# 1-0\n"));
+    assert!(pgn.lines().all(|line| line.len() < 80));
+
+    let read = read_pgn(&pgn).unwrap().remove(0);
+    assert_eq!(Some("Philidor \"Defence\""), read.tag("Opening"));
+    assert_eq!(game.to_fen(), read.game.to_fen());
+
+    let game = ChessGame::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 30").unwrap();
+    let pgn = write_pgn(&game, &[]);
+    assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 30\"]\n"));
+    assert!(pgn.ends_with("\n\n*\n"));
+}
+
+#[test]
+fn writes_variants() {
+    let mut game = ChessGame::new(Capablanca).unwrap();
+    for san in ["d4", "d5", "Af4"] {
+        game.move_piece(san.to_string()).unwrap();
+    }
+    let pgn = write_pgn(&game, &[("Variant", "ignored")]);
+    assert!(pgn.contains("[Variant \"Capablanca\"]\n\n"));
+    assert!(!pgn.contains("ignored"));
+    assert!(pgn.ends_with("\n\n1. d4 d5 2. Af4 *\n"));
+    let read = read_pgn(&pgn).unwrap().remove(0);
+    assert_eq!("Capablanca", read.game.variant().name());
+    assert_eq!(game.to_fen(), read.game.to_fen());
+
+    let game = ChessGame::new(Chess960(0)).unwrap();
+    let pgn = write_pgn(&game, &[]);
+    assert!(pgn.contains("[Variant \"Chess960\"]\n[SetUp \"1\"]\n"));
+    let read = read_pgn(&pgn).unwrap().remove(0);
+    assert_eq!("Chess960", read.game.variant().name());
+    assert_eq!(game.to_fen(), read.game.to_fen());
+
+    let fen = "4k3/8/8/8/3A4/8/8/4K3 w - - 0 1";
+    let game = ChessGame::from_fen_with_pieces(fen, &[PieceDefinition::archbishop()]).unwrap();
+    assert!(write_pgn(&game, &[]).contains(fen));
+
+    // without a FEN tag a variant starts from its own start position
+    let read = read_pgn("[Variant \"capablanca\"]\n\n1. Ci3 *\n").unwrap();
+    assert_eq!("Capablanca", read[0].game.variant().name());
+    assert!(matches!(
+        read_pgn("[Variant \"Losers\"]\n\n1. e4 *\n"),
+        Err(ChessError::BadPgn { reason, .. }) if reason == "Unknown chess variant \"Losers\""
+    ));
+}
+
+#[test]
+fn writes_three_check() {
+    let mut game = ChessGame::new(ThreeCheck).unwrap();
+    for san in [
+        "e4", "e5", "Bc4", "Nc6", "Bxf7+", "Kxf7", "Qh5+", "Ke7", "Qxe5+",
+    ] {
+        game.move_piece(san.to_string()).unwrap();
+    }
+    let pgn = write_pgn(&game, &[]);
+    assert!(pgn.contains("[Result \"1-0\"]\n[Variant \"Three-check\"]\n\n1. e4"));
+    assert!(pgn.ends_with("5. Qxe5+ 1-0\n"));
+    let read = read_pgn(&pgn).unwrap().remove(0);
+    assert_eq!("Three-check", read.game.variant().name());
+    assert_eq!(3, read.game.checks_given(Color::White));
+    assert_eq!(game.status(), read.game.status());
+    assert_eq!(game.to_fen(), read.game.to_fen());
+
+    let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 2+3 0 1";
+    let game = ChessGame::from_variant_fen(ThreeCheck, fen).unwrap();
+    assert!(write_pgn(&game, &[]).contains(fen));
+
+    let game = ChessGame::new(KingOfTheHill).unwrap();
+    let read = read_pgn(&write_pgn(&game, &[])).unwrap().remove(0);
+    assert_eq!("King of the Hill", read.game.variant().name());
+}
+
+#[test]
+fn reports_errors() {
+    let error = |input: &str| match read_pgn(input) {
+        Err(ChessError::BadPgn {
+            line,
+            column,
+            reason,
+        }) => (line, column, reason),
+        _ => panic!("expected an error for {:?}", input),
+    };
+    assert_eq!(
+        (
+            2,
+            4,
+            "illegal move Nf6: No legal move matches \"Nf6\"".to_string()
+        ),
+        error("1. e4 e5\n2. Nf6 *")
+    );
+    assert_eq!((1, 23), {
+        let (line, column, _) = error("1. e4 e5 2. Nc3 Nc6 3.Nc3 *");
+        (line, column)
+    });
+    assert_eq!(
+        (1, 10, "comment is never closed".to_string()),
+        error("1. e4 e5 {unfinished")
+    );
+    assert_eq!(
+        (1, 10, "variation is never closed".to_string()),
+        error("1. e4 e5 (1... c5 *")
+    );
+    assert_eq!(
+        (2, 1, "malformed tag pair".to_string()),
+        error("[Event \"?\"]\n[White \"?]\n")
+    );
+}
@@ -0,0 +1,298 @@
+use super::*;
+use crate::ChessError;
+
+/// How far a movement carries a piece along each of its directions.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum Reach {
+    /// A single step, jumping over anything in between, like the knight.
+    Leap,
+    /// Step after step until the edge or the first piece, which it may take,
+    /// like the rook.
+    Ride,
+    /// Step after step over empty squares, then over the first piece in the
+    /// way to the square right behind it, like the grasshopper.
+    Hop,
+}
+
+/// One way a piece moves: a step in all its rotations and reflections, or
+/// some of them, taken as a leap, ride or hop. Directions are seen from
+/// White's side and turned around for Black.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct Movement {
+    step: (isize, isize),
+    directions: Vec<(isize, isize)>,
+    reach: Reach,
+    moves: bool,
+    captures: bool,
+}
+
+impl Movement {
+    /// Moves and captures along every rotation and reflection of `step`.
+    pub fn new(step: (isize, isize), reach: Reach) -> Movement {
+        Movement {
+            step,
+            directions: symmetric_steps(step),
+            reach,
+            moves: true,
+            captures: true,
+        }
+    }
+
+    pub fn leap(step: (isize, isize)) -> Movement {
+        Movement::new(step, Reach::Leap)
+    }
+
+    pub fn ride(step: (isize, isize)) -> Movement {
+        Movement::new(step, Reach::Ride)
+    }
+
+    pub fn hop(step: (isize, isize)) -> Movement {
+        Movement::new(step, Reach::Hop)
+    }
+
+    /// Keeps only the directions towards the opponent.
+    pub fn forward(self) -> Movement {
+        self.towards(true, false)
+    }
+
+    /// Keeps only the directions towards the own side.
+    pub fn backward(self) -> Movement {
+        self.towards(false, true)
+    }
+
+    /// Only goes to empty squares, like a pawn moving straight ahead.
+    pub fn move_only(self) -> Movement {
+        Movement {
+            captures: false,
+            ..self
+        }
+    }
+
+    /// Only goes to squares it captures on, like a pawn moving diagonally.
+    pub fn capture_only(self) -> Movement {
+        Movement {
+            moves: false,
+            ..self
+        }
+    }
+
+    fn towards(mut self, forward: bool, backward: bool) -> Movement {
+        self.directions
+            .retain(|&(_, y)| (forward && y > 0) || (backward && y < 0));
+        self
+    }
+
+    pub fn step(&self) -> (isize, isize) {
+        self.step
+    }
+
+    pub fn directions(&self) -> &[(isize, isize)] {
+        &self.directions
+    }
+
+    pub fn reach(&self) -> Reach {
+        self.reach
+    }
+
+    pub fn moves(&self) -> bool {
+        self.moves
+    }
+
+    pub fn captures(&self) -> bool {
+        self.captures
+    }
+}
+
+/// A piece beyond the six standard ones, e.g. the archbishop or the camel,
+/// told apart from them and from each other by its letter.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct PieceDefinition {
+    name: String,
+    letter: char,
+    symbols: [char; 2],
+    svg: Option<String>,
+    movements: Vec<Movement>,
+}
+
+impl PieceDefinition {
+    /// A piece written as `letter` in FEN and algebraic notation, in upper
+    /// case for White and lower case for Black. The letters of the standard
+    /// pieces are taken.
+    pub fn new(
+        name: &str,
+        letter: char,
+        movements: Vec<Movement>,
+    ) -> Result<PieceDefinition, ChessError> {
+        let invalid = |reason: String| Err(ChessError::InvalidPieceDefinition(reason));
+        let letter = letter.to_ascii_uppercase();
+        if !letter.is_ascii_uppercase() {
+            return invalid(format!("{} needs a letter, not '{}'", name, letter));
+        }
+        if PieceType::from_letter(letter).is_some() {
+            return invalid(format!(
+                "{} cannot use '{}', a standard piece's letter",
+                name, letter
+            ));
+        }
+        if movements.is_empty() {
+            return invalid(format!("{} cannot move", name));
+        }
+        Ok(PieceDefinition {
+            name: name.to_string(),
+            letter,
+            symbols: [letter, letter.to_ascii_lowercase()],
+            svg: None,
+            movements,
+        })
+    }
+
+    /// Reads the movements from Betza's funny notation, e.g. "BN" for the
+    /// archbishop. Understood are the leapers W, F, D, N, A, H, C, Z and G,
+    /// K for W and F, the riders R, B and Q or any leaper written twice, e.g.
+    /// "NN" for the nightrider, and the prefixes f and b for forward and
+    /// backward only, m for moving without capturing, c for capturing only
+    /// and g for hopping like the grasshopper, "gQ".
+    pub fn from_betza(
+        name: &str,
+        letter: char,
+        betza: &str,
+    ) -> Result<PieceDefinition, ChessError> {
+        let invalid = || {
+            ChessError::InvalidPieceDefinition(format!(
+                "{:?} is not understood Betza notation",
+                betza
+            ))
+        };
+        let mut movements = Vec::new();
+        let mut chars = betza.chars().peekable();
+        while chars.peek().is_some() {
+            let mut prefixes = String::new();
+            while let Some(prefix) = chars.next_if(|c| "fbmcg".contains(*c)) {
+                prefixes.push(prefix);
+            }
+            let atom = chars.next().ok_or_else(invalid)?;
+            let doubled = chars.next_if_eq(&atom).is_some();
+            let (steps, rides): (&[(isize, isize)], bool) = match atom {
+                'K' => (&[(1, 0), (1, 1)], false),
+                'R' => (&[(1, 0)], true),
+                'B' => (&[(1, 1)], true),
+                'Q' => (&[(1, 0), (1, 1)], true),
+                _ => {
+                    let step = BETZA_LEAPERS
+                        .iter()
+                        .find(|(leaper, _)| *leaper == atom)
+                        .map(|(_, step)| step)
+                        .ok_or_else(invalid)?;
+                    (std::slice::from_ref(step), false)
+                }
+            };
+            if doubled && rides {
+                return Err(invalid());
+            }
+            for &step in steps {
+                let reach = if prefixes.contains('g') {
+                    Reach::Hop
+                } else if rides || doubled {
+                    Reach::Ride
+                } else {
+                    Reach::Leap
+                };
+                let mut movement = Movement::new(step, reach);
+                let forward = prefixes.contains('f');
+                let backward = prefixes.contains('b');
+                if forward || backward {
+                    movement = movement.towards(forward, backward);
+                }
+                if prefixes.contains('m') {
+                    movement = movement.move_only();
+                } else if prefixes.contains('c') {
+                    movement = movement.capture_only();
+                }
+                movements.push(movement);
+            }
+        }
+        PieceDefinition::new(name, letter, movements)
+    }
+
+    /// Moves as bishop and knight, as in Capablanca chess.
+    pub fn archbishop() -> PieceDefinition {
+        PieceDefinition::from_betza("Archbishop", 'A', "BN").unwrap()
+    }
+
+    /// Moves as rook and knight, as in Capablanca chess.
+    pub fn chancellor() -> PieceDefinition {
+        PieceDefinition::from_betza("Chancellor", 'C', "RN").unwrap()
+    }
+
+    /// Leaps three squares one way and one the other.
+    pub fn camel() -> PieceDefinition {
+        PieceDefinition::from_betza("Camel", 'L', "C").unwrap()
+    }
+
+    /// Hops along queen lines over the first piece in the way.
+    pub fn grasshopper() -> PieceDefinition {
+        PieceDefinition::from_betza("Grasshopper", 'G', "gQ").unwrap()
+    }
+
+    /// The characters the piece is shown as for White and Black, its letter
+    /// in upper and lower case unless set otherwise.
+    pub fn with_symbols(self, white: char, black: char) -> PieceDefinition {
+        PieceDefinition {
+            symbols: [white, black],
+            ..self
+        }
+    }
+
+    /// An SVG image for graphical boards, which fill it with the piece's color.
+    pub fn with_svg(self, svg: &str) -> PieceDefinition {
+        PieceDefinition {
+            svg: Some(svg.to_string()),
+            ..self
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn letter(&self) -> char {
+        self.letter
+    }
+
+    pub fn symbol(&self, color: Color) -> char {
+        self.symbols[if color == Color::White { 0 } else { 1 }]
+    }
+
+    pub fn svg(&self) -> Option<&str> {
+        self.svg.as_deref()
+    }
+
+    pub fn movements(&self) -> &[Movement] {
+        &self.movements
+    }
+}
+
+const BETZA_LEAPERS: [(char, (isize, isize)); 9] = [
+    ('W', (1, 0)),
+    ('F', (1, 1)),
+    ('D', (2, 0)),
+    ('N', (2, 1)),
+    ('A', (2, 2)),
+    ('H', (3, 0)),
+    ('C', (3, 1)),
+    ('Z', (3, 2)),
+    ('G', (3, 3)),
+];
+
+/// Every rotation and reflection of `step`, e.g. all eight knight jumps.
+pub fn symmetric_steps((x, y): (isize, isize)) -> Vec<(isize, isize)> {
+    let mut steps = Vec::new();
+    for &(x, y) in &[(x, y), (y, x)] {
+        for &step in &[(x, y), (x, -y), (-x, y), (-x, -y)] {
+            if !steps.contains(&step) {
+                steps.push(step);
+            }
+        }
+    }
+    steps
+}
@@ -1,11 +1,24 @@
-use std::fmt;
+use std::{fmt, sync::Arc};
 
-#[derive(PartialEq, Copy, Clone)]
+mod fairy;
+
+pub use fairy::{symmetric_steps, Movement, PieceDefinition, Reach};
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub enum Color {
     White,
     Black,
 }
-#[derive(PartialEq, Clone, Debug)]
+
+impl Color {
+    pub fn opposite(self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub enum PieceType {
     Pawn,
     Rook,
@@ -13,12 +26,15 @@ pub enum PieceType {
     Bishop,
     King,
     Queen,
+    /// A fairy piece, moving however its definition says.
+    Custom(Arc<PieceDefinition>),
 }
 
-#[derive(Clone)]
+#[derive(PartialEq, Clone, Debug)]
 pub struct Piece {
     pub color: Color,
     pub piece_type: PieceType,
+    /// For custom pieces the steps of their first two movements.
     pub movement: ((isize, isize), Option<(isize, isize)>),
     pub has_moved: bool,
     pub moves_continous: bool,
@@ -33,11 +49,44 @@ impl fmt::Display for PieceType {
             PieceType::Bishop => 'B',
             PieceType::King => 'K',
             PieceType::Queen => 'Q',
+            PieceType::Custom(definition) => definition.letter(),
         };
         write!(f, "{}", symbol)
     }
 }
 
+impl PieceType {
+    pub fn letter(&self) -> char {
+        match self {
+            PieceType::Pawn => 'P',
+            PieceType::Rook => 'R',
+            PieceType::Knight => 'N',
+            PieceType::Bishop => 'B',
+            PieceType::King => 'K',
+            PieceType::Queen => 'Q',
+            PieceType::Custom(definition) => definition.letter(),
+        }
+    }
+
+    pub fn custom(definition: PieceDefinition) -> PieceType {
+        PieceType::Custom(Arc::new(definition))
+    }
+
+    /// Reads a standard piece letter in either case, e.g. 'N' or 'n' for a
+    /// knight. Custom pieces are looked up through the board they are on.
+    pub fn from_letter(letter: char) -> Option<PieceType> {
+        match letter.to_ascii_uppercase() {
+            'P' => Some(PieceType::Pawn),
+            'R' => Some(PieceType::Rook),
+            'N' => Some(PieceType::Knight),
+            'B' => Some(PieceType::Bishop),
+            'K' => Some(PieceType::King),
+            'Q' => Some(PieceType::Queen),
+            _ => None,
+        }
+    }
+}
+
 impl Piece {
     pub fn moved(&mut self) {
         if !self.has_moved {
@@ -48,24 +97,20 @@ impl Piece {
 
 impl fmt::Display for Piece {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let symbol: char = if self.color == Color::White {
-            match self.piece_type {
-                PieceType::Pawn => '\u{265F}',
-                PieceType::Rook => '\u{265C}',
-                PieceType::Knight => '\u{265E}',
-                PieceType::Bishop => '\u{265D}',
-                PieceType::King => '\u{265A}',
-                PieceType::Queen => '\u{265B}',
-            }
-        } else {
-            match self.piece_type {
-                PieceType::Pawn => '\u{2659}',
-                PieceType::Rook => '\u{2656}',
-                PieceType::Knight => '\u{2658}',
-                PieceType::Bishop => '\u{2657}',
-                PieceType::King => '\u{2654}',
-                PieceType::Queen => '\u{2655}',
-            }
+        let symbol: char = match (&self.piece_type, self.color) {
+            (PieceType::Pawn, Color::White) => '\u{265F}',
+            (PieceType::Rook, Color::White) => '\u{265C}',
+            (PieceType::Knight, Color::White) => '\u{265E}',
+            (PieceType::Bishop, Color::White) => '\u{265D}',
+            (PieceType::King, Color::White) => '\u{265A}',
+            (PieceType::Queen, Color::White) => '\u{265B}',
+            (PieceType::Pawn, Color::Black) => '\u{2659}',
+            (PieceType::Rook, Color::Black) => '\u{2656}',
+            (PieceType::Knight, Color::Black) => '\u{2658}',
+            (PieceType::Bishop, Color::Black) => '\u{2657}',
+            (PieceType::King, Color::Black) => '\u{2654}',
+            (PieceType::Queen, Color::Black) => '\u{2655}',
+            (PieceType::Custom(definition), color) => definition.symbol(color),
         };
         write!(f, "{}", symbol)
     }
@@ -82,6 +127,10 @@ pub fn piece_make(color: Color, piece_type: PieceType) -> Piece {
             PieceType::Bishop => ((1, 1), None),
             PieceType::King => ((0, 1), Some((1, 1))),
             PieceType::Queen => ((0, 1), Some((1, 1))),
+            PieceType::Custom(definition) => {
+                let mut steps = definition.movements().iter().map(Movement::step);
+                (steps.next().unwrap(), steps.next())
+            }
         },
         moves_continous: match &piece_type {
             PieceType::Pawn => false,
@@ -90,6 +139,7 @@ pub fn piece_make(color: Color, piece_type: PieceType) -> Piece {
             PieceType::Bishop => true,
             PieceType::King => false,
             PieceType::Queen => true,
+            PieceType::Custom(definition) => definition.movements()[0].reach() != Reach::Leap,
         },
         piece_type,
     }
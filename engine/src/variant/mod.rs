@@ -0,0 +1,246 @@
+use crate::board_logic::*;
+use crate::chess_game::*;
+use crate::piece_logic::*;
+use crate::{ChessError, Move, Square};
+
+#[cfg(test)]
+mod tests;
+
+/// The rules of a kind of chess beyond how the pieces move: where the game
+/// starts, which moves are allowed, how it ends and what pawns promote to.
+/// Every method but `name` and `start_position` defaults to standard chess,
+/// so a variant only spells out where it differs.
+pub trait Variant {
+    /// The name of the variant as in PGN's "Variant" tag, e.g. "Chess960".
+    fn name(&self) -> &str;
+
+    /// The board the game starts on, with White to move.
+    fn start_position(&self) -> Result<ChessBoard, ChessError>;
+
+    /// The custom pieces played with, so FEN positions can name them.
+    fn pieces(&self) -> Vec<PieceDefinition> {
+        Vec::new()
+    }
+
+    /// The pieces a pawn may promote to, the first one being the usual choice.
+    fn promotion_choices(&self) -> Vec<PieceType> {
+        PROMOTION_CHOICES.to_vec()
+    }
+
+    /// Whether `mov`, legal on `board` by how the pieces move and the king
+    /// is kept out of check, may be played in this variant.
+    fn allows(&self, _board: &ChessBoard, _mov: &Move) -> bool {
+        true
+    }
+
+    /// How `game` stands after a move, or at its start.
+    fn status(&self, game: &ChessGame) -> GameStatus {
+        game.standard_status()
+    }
+
+    /// Whether neither side can win any more, which ends the game in a draw.
+    fn insufficient_material(&self, board: &ChessBoard) -> bool {
+        board.has_insufficient_material()
+    }
+
+    /// The number of checks that wins the game, if any. FEN positions then
+    /// count the checks left to give, see `split_check_counters`.
+    fn check_limit(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// The variant called `name`, in any case, as in PGN's "Variant" tag. As
+/// its start position comes with the game, Chess960 is found as start
+/// position 518, the standard one.
+pub fn variant_by_name(name: &str) -> Option<Box<dyn Variant>> {
+    let variants: Vec<Box<dyn Variant>> = vec![
+        Box::new(Standard),
+        Box::new(Chess960(518)),
+        Box::new(Capablanca),
+        Box::new(ThreeCheck),
+        Box::new(KingOfTheHill),
+    ];
+    variants
+        .into_iter()
+        .find(|variant| variant.name().eq_ignore_ascii_case(name))
+}
+
+impl<V: Variant + ?Sized> Variant for Box<V> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn start_position(&self) -> Result<ChessBoard, ChessError> {
+        (**self).start_position()
+    }
+
+    fn pieces(&self) -> Vec<PieceDefinition> {
+        (**self).pieces()
+    }
+
+    fn promotion_choices(&self) -> Vec<PieceType> {
+        (**self).promotion_choices()
+    }
+
+    fn allows(&self, board: &ChessBoard, mov: &Move) -> bool {
+        (**self).allows(board, mov)
+    }
+
+    fn status(&self, game: &ChessGame) -> GameStatus {
+        (**self).status(game)
+    }
+
+    fn insufficient_material(&self, board: &ChessBoard) -> bool {
+        (**self).insufficient_material(board)
+    }
+
+    fn check_limit(&self) -> Option<usize> {
+        (**self).check_limit()
+    }
+}
+
+/// Chess as played under the FIDE laws.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct Standard;
+
+impl Variant for Standard {
+    fn name(&self) -> &str {
+        "Standard"
+    }
+
+    fn start_position(&self) -> Result<ChessBoard, ChessError> {
+        let mut board = init_board();
+        board.standard_pieces(Color::White);
+        board.standard_pieces(Color::Black);
+        Ok(board)
+    }
+}
+
+/// Fischer random chess from start position `0`, 0 to 959, see
+/// `chess960_back_rank`.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Chess960(pub usize);
+
+impl Variant for Chess960 {
+    fn name(&self) -> &str {
+        "Chess960"
+    }
+
+    fn start_position(&self) -> Result<ChessBoard, ChessError> {
+        let mut board = init_board();
+        board.chess960_pieces(Color::White, self.0)?;
+        board.chess960_pieces(Color::Black, self.0)?;
+        Ok(board)
+    }
+}
+
+/// Capablanca chess on a 10x8 board with the archbishop between knight and
+/// bishop and the chancellor between bishop and knight on the king side,
+/// both of which pawns may promote to.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct Capablanca;
+
+impl Variant for Capablanca {
+    fn name(&self) -> &str {
+        "Capablanca"
+    }
+
+    fn start_position(&self) -> Result<ChessBoard, ChessError> {
+        let fen = "rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1";
+        parse_fen_with_pieces(fen, &self.pieces()).map(|(board, ..)| board)
+    }
+
+    fn pieces(&self) -> Vec<PieceDefinition> {
+        vec![PieceDefinition::archbishop(), PieceDefinition::chancellor()]
+    }
+
+    fn promotion_choices(&self) -> Vec<PieceType> {
+        let mut choices = PROMOTION_CHOICES.to_vec();
+        choices.extend(self.pieces().into_iter().map(PieceType::custom));
+        choices
+    }
+}
+
+/// Standard chess also won by giving check three times.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct ThreeCheck;
+
+impl Variant for ThreeCheck {
+    fn name(&self) -> &str {
+        "Three-check"
+    }
+
+    fn start_position(&self) -> Result<ChessBoard, ChessError> {
+        Standard.start_position()
+    }
+
+    fn status(&self, game: &ChessGame) -> GameStatus {
+        for &color in &[Color::White, Color::Black] {
+            if game.checks_given(color) >= 3 {
+                return GameStatus::VariantWin {
+                    winner: color,
+                    reason: "three checks",
+                };
+            }
+        }
+        game.standard_status()
+    }
+
+    /// Any piece besides the king can still give check.
+    fn insufficient_material(&self, board: &ChessBoard) -> bool {
+        board
+            .dimensions()
+            .squares()
+            .filter_map(|position| board.ref_piece(position))
+            .all(|piece| piece.piece_type == PieceType::King)
+    }
+
+    fn check_limit(&self) -> Option<usize> {
+        Some(3)
+    }
+}
+
+/// Standard chess also won by bringing the king to one of the four
+/// center squares, d4, e4, d5 and e5 on a standard board.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct KingOfTheHill;
+
+impl KingOfTheHill {
+    /// Whether `position` is one of the center squares of `board`.
+    pub fn is_hill(board: &ChessBoard, position: Square) -> bool {
+        let dims = board.dimensions();
+        let center = |squares: usize, at: usize| (squares - 1) / 2 <= at && at <= squares / 2;
+        center(dims.files(), position.file()) && center(dims.ranks(), position.rank())
+    }
+}
+
+impl Variant for KingOfTheHill {
+    fn name(&self) -> &str {
+        "King of the Hill"
+    }
+
+    fn start_position(&self) -> Result<ChessBoard, ChessError> {
+        Standard.start_position()
+    }
+
+    fn status(&self, game: &ChessGame) -> GameStatus {
+        let board = game.chess_board();
+        for &color in &[Color::White, Color::Black] {
+            if let Some(king_pos) = board.king_position(color) {
+                if KingOfTheHill::is_hill(board, king_pos) {
+                    return GameStatus::VariantWin {
+                        winner: color,
+                        reason: "reaching the hill",
+                    };
+                }
+            }
+        }
+        game.standard_status()
+    }
+
+    /// A lone king can still walk to the hill.
+    fn insufficient_material(&self, _board: &ChessBoard) -> bool {
+        false
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{board_logic::*, chess_game::*, move_logic::SpecialMove, variant::*, *};
+
+/// Standard chess in which the king may not capture.
+struct TimidKings;
+
+impl Variant for TimidKings {
+    fn name(&self) -> &str {
+        "Timid kings"
+    }
+
+    fn start_position(&self) -> Result<ChessBoard, ChessError> {
+        Standard.start_position()
+    }
+
+    fn allows(&self, board: &ChessBoard, mov: &Move) -> bool {
+        let king_moves = board
+            .ref_piece(mov.from)
+            .is_some_and(|piece| piece.piece_type == PieceType::King);
+        !king_moves || board.captured_piece(mov).is_none()
+    }
+}
+
+/// Standard chess without castling.
+struct NoCastling;
+
+impl Variant for NoCastling {
+    fn name(&self) -> &str {
+        "No castling"
+    }
+
+    fn start_position(&self) -> Result<ChessBoard, ChessError> {
+        Standard.start_position()
+    }
+
+    fn allows(&self, _board: &ChessBoard, mov: &Move) -> bool {
+        !matches!(
+            mov.kind,
+            Some(SpecialMove::CastlingLeft) | Some(SpecialMove::CastlingRight)
+        )
+    }
+}
+
+#[test]
+fn standard_rules() {
+    let game = ChessGame::new(Standard).unwrap();
+    assert_eq!("Standard", game.variant().name());
+    assert_eq!(init_standard_chess().to_fen(), game.to_fen());
+    assert_eq!(&PROMOTION_CHOICES, game.chess_board().promotion_choices());
+    assert_eq!(
+        init_chess960(518).unwrap().to_fen(),
+        ChessGame::new(Chess960(518)).unwrap().to_fen()
+    );
+    assert!(ChessGame::new(Chess960(960)).is_err());
+}
+
+#[test]
+fn filters_moves() {
+    let fen = "4k3/8/8/8/8/8/3p4/4K3 w - - 0 1";
+    assert_eq!(5, ChessGame::from_fen(fen).unwrap().legal_moves().len());
+    let mut game = ChessGame::from_variant_fen(TimidKings, fen).unwrap();
+    assert_eq!(4, game.legal_moves().len());
+    assert!(!game
+        .pick_square("e1".parse().unwrap())
+        .unwrap()
+        .iter()
+        .any(|mov| mov.to.to_string() == "d2"));
+    assert!(matches!(
+        game.play_uci("e1d2"),
+        Err(ChessError::IllegalMove { .. })
+    ));
+
+    // with its only capture forbidden, the king has no move left
+    let game = ChessGame::from_variant_fen(TimidKings, "8/8/8/8/8/2k5/1q6/K7 w - - 0 1");
+    assert_eq!(
+        GameStatus::Checkmate {
+            winner: Color::Black
+        },
+        game.unwrap().status()
+    );
+}
+
+#[test]
+fn filters_moves_by_kind() {
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    let mut game = ChessGame::from_variant_fen(NoCastling, fen).unwrap();
+    assert!(!game.legal_moves().iter().any(|mov| mov.kind.is_some()));
+    assert!(matches!(
+        game.move_piece("e1 g1".to_string()),
+        Err(ChessError::IllegalMove { .. })
+    ));
+    let castling = Move::new("e1".parse().unwrap(), "c1".parse().unwrap());
+    assert!(matches!(
+        game.play_move(castling),
+        Err(ChessError::IllegalMove { .. })
+    ));
+    game.move_piece("e1 f1".to_string()).unwrap();
+}
+
+#[test]
+fn capablanca() {
+    let game = ChessGame::new(Capablanca).unwrap();
+    assert_eq!(Dimensions::new(10, 8).unwrap(), game.dimensions());
+    assert_eq!(28, game.legal_moves().len());
+    assert_eq!(
+        "rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1",
+        game.to_fen()
+    );
+
+    let mut game =
+        ChessGame::from_variant_fen(Capablanca, "4k5/1P8/10/10/10/10/10/4K5 w - - 0 1").unwrap();
+    assert_eq!(6, game.pick_square("b7".parse().unwrap()).unwrap().len());
+    assert_eq!(Ok("b8=C+".to_string()), game.move_piece("b8=C".to_string()));
+    assert_eq!("1C2k5/10/10/10/10/10/10/4K5 b - - 0 1", game.to_fen());
+
+    let fen = "4k5/1P8/10/10/10/10/10/4K5 w - - 0 1";
+    let mut game = ChessGame::from_variant_fen(Capablanca, fen).unwrap();
+    game.move_piece("b7b8a".to_string()).unwrap();
+    assert_eq!(vec!["b7b8a".to_string()], game.uci_moves());
+    let mut replayed = ChessGame::from_variant_fen(Capablanca, fen).unwrap();
+    for uci in game.uci_moves() {
+        replayed.play_uci(&uci).unwrap();
+    }
+    assert_eq!(game.to_fen(), replayed.to_fen());
+    assert_eq!(
+        Err(ChessError::BadNotation("b7b8k".to_string())),
+        replayed.chess_board().parse_uci("b7b8k")
+    );
+}
+
+#[test]
+fn check_counters() {
+    let fen = "4k3/8/8/8/8/8/8/4K3 w - - 0 1";
+    assert_eq!(
+        Ok((fen.to_string(), [1, 0])),
+        split_check_counters("4k3/8/8/8/8/8/8/4K3 w - - 2+3 0 1", 3)
+    );
+    assert_eq!(
+        Ok((fen.to_string(), [2, 1])),
+        split_check_counters("4k3/8/8/8/8/8/8/4K3 w - - 0 1 +2+1", 3)
+    );
+    assert_eq!(Ok((fen.to_string(), [0, 0])), split_check_counters(fen, 3));
+    for bad in ["4+3", "3+", "+1+1", "a+1"] {
+        let fen = format!("4k3/8/8/8/8/8/8/4K3 w - - {} 0 1", bad);
+        assert!(matches!(
+            split_check_counters(&fen, 3),
+            Err(ChessError::BadFen(_))
+        ));
+    }
+    assert_eq!(
+        "4k3/8/8/8/8/8/8/4K3 w - - 2+3 0 1",
+        join_check_counters(fen, [1, 0], 3)
+    );
+}
+
+#[test]
+fn king_of_the_hill() {
+    let fen = "4k3/8/8/8/8/2K5/8/8 w - - 0 1";
+    assert!(ChessGame::from_fen(fen).unwrap().status().is_over());
+    let mut game = ChessGame::from_variant_fen(KingOfTheHill, fen).unwrap();
+    assert_eq!(GameStatus::Ongoing, game.status());
+    assert_eq!(
+        Ok("Game is over! White wins by reaching the hill!".to_string()),
+        game.move_piece("Kd4".to_string())
+    );
+    assert_eq!(
+        GameStatus::VariantWin {
+            winner: Color::White,
+            reason: "reaching the hill"
+        },
+        game.status()
+    );
+    let board = game.chess_board();
+    let hill: Vec<String> = board
+        .dimensions()
+        .squares()
+        .filter(|position| KingOfTheHill::is_hill(board, *position))
+        .map(|position| position.to_string())
+        .collect();
+    assert_eq!(vec!["d4", "e4", "d5", "e5"], hill);
+}
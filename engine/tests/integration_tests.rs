@@ -1,26 +1,79 @@
-use maltebl_chess::chess_game::*;
+use maltebl_chess::{
+    chess_game::*,
+    piece_logic::{Color, PieceDefinition, PieceType},
+    variant::ThreeCheck,
+    ChessError, Dimensions, Square,
+};
 #[test]
 fn test_chessgame() {
     let mut chess = init_standard_chess();
     chess.move_piece("f2 f3".to_string()).unwrap();
     chess.move_piece("e7 e5".to_string()).unwrap();
     chess.move_piece("g2 g4".to_string()).unwrap();
+    assert!(chess
+        .move_piece("d8 h4".to_string())
+        .unwrap()
+        .contains("checkmate"));
+    chess.print_board();
     assert_eq!(
-        true,
-        chess
-            .move_piece("d8 h4".to_string())
-            .unwrap()
-            .contains("checkmate")
+        GameStatus::Checkmate {
+            winner: Color::Black
+        },
+        chess.status()
+    );
+    assert_eq!(
+        Err(ChessError::GameOver),
+        chess.move_piece("a2 a3".to_string())
+    );
+}
+
+#[test]
+fn resignation() {
+    let mut chess = init_standard_chess();
+    chess.move_piece("e2 e4".to_string()).unwrap();
+    assert_eq!(
+        Ok(GameStatus::Resignation {
+            winner: Color::White
+        }),
+        chess.resign(Color::Black)
+    );
+    assert_eq!(Err(ChessError::GameOver), chess.agree_draw());
+    assert_eq!(
+        Err(ChessError::GameOver),
+        chess.move_piece("e7 e5".to_string())
     );
-    chess.print_board();
 }
 
 #[test]
-#[should_panic(expected = "That is not your piece!")]
 fn test_turns() {
     let mut chess = init_standard_chess();
     chess.move_piece("f2 f3".to_string()).unwrap();
-    chess.move_piece("g2 g4".to_string()).unwrap();
+    assert_eq!(
+        Err(ChessError::NotYourPiece),
+        chess.move_piece("g2 g4".to_string())
+    );
+}
+
+#[test]
+fn error_kinds() {
+    let mut chess = init_standard_chess();
+    assert_eq!(
+        Err(ChessError::EmptySquare("e4".parse().unwrap())),
+        chess.move_piece("e4 e5".to_string())
+    );
+    assert_eq!(
+        Err(ChessError::BadNotation("e9".to_string())),
+        chess.move_piece("e9".to_string())
+    );
+    match chess.move_piece("e2 e5".to_string()) {
+        Err(ChessError::IllegalMove { from, to, .. }) => {
+            assert_eq!(
+                (from, to),
+                (Square::new(4, 1).unwrap(), Square::new(4, 4).unwrap())
+            )
+        }
+        other => panic!("expected illegal move, got {:?}", other),
+    }
 }
 
 #[test]
@@ -31,3 +84,507 @@ fn pick_piece() {
         chess.pick_piece("f2".to_string()).unwrap()
     )
 }
+
+#[test]
+fn legal_moves_for_side_to_move() {
+    let mut chess = init_standard_chess();
+    assert_eq!(20, chess.legal_moves().len());
+    chess.move_piece("e2 e4".to_string()).unwrap();
+    assert!(chess.legal_moves().iter().all(|mov| mov.from.rank() >= 6));
+}
+
+fn shuffle_knights(chess: &mut ChessGame) {
+    for mov in &["g1 f3", "g8 f6", "f3 g1", "f6 g8"] {
+        chess.move_piece(mov.to_string()).unwrap();
+    }
+}
+
+#[test]
+fn threefold_repetition() {
+    let mut chess = init_standard_chess();
+    shuffle_knights(&mut chess);
+    assert_eq!(2, chess.repetition_count());
+    assert_eq!(4, chess.halfmove_clock());
+    assert_eq!(Err(ChessError::NoDrawToClaim), chess.claim_draw());
+    shuffle_knights(&mut chess);
+    assert_eq!(3, chess.repetition_count());
+    assert_eq!(GameStatus::Ongoing, chess.status());
+    assert_eq!(
+        Some(DrawReason::ThreefoldRepetition),
+        chess.claimable_draw()
+    );
+    assert_eq!(
+        Ok(GameStatus::Draw {
+            reason: DrawReason::ThreefoldRepetition
+        }),
+        chess.claim_draw()
+    );
+}
+
+#[test]
+fn automatic_repetition_draw() {
+    let mut chess = init_standard_chess();
+    chess.set_automatic_draws(true);
+    chess.move_piece("e2 e4".to_string()).unwrap();
+    chess.move_piece("e7 e5".to_string()).unwrap();
+    assert_eq!(0, chess.halfmove_clock());
+    shuffle_knights(&mut chess);
+    shuffle_knights(&mut chess);
+    assert_eq!(
+        GameStatus::Draw {
+            reason: DrawReason::ThreefoldRepetition
+        },
+        chess.status()
+    );
+}
+
+#[test]
+fn fivefold_repetition() {
+    let mut chess = init_standard_chess();
+    chess.set_automatic_draws(false);
+    for _ in 0..3 {
+        shuffle_knights(&mut chess);
+    }
+    assert_eq!(GameStatus::Ongoing, chess.status());
+    shuffle_knights(&mut chess);
+    assert_eq!(5, chess.repetition_count());
+    assert_eq!(
+        GameStatus::Draw {
+            reason: DrawReason::FivefoldRepetition
+        },
+        chess.status()
+    );
+    assert_eq!(Err(ChessError::GameOver), chess.claim_draw());
+}
+
+/// Plays `plies` quiet knight moves, keeping each side's knights on its own
+/// half of the board and always heading for the position, of those in
+/// `seen`, seen least often.
+fn wander_knights(chess: &mut ChessGame, plies: usize, seen: &mut Vec<Vec<Square>>) {
+    for _ in 0..plies {
+        let board = chess.get_board();
+        let knights: Vec<Square> = (0..8)
+            .flat_map(|rank| (0..8).map(move |file| Square::new(file, rank).unwrap()))
+            .filter(|position| {
+                board[position.rank()][position.file()]
+                    .as_ref()
+                    .is_some_and(|piece| piece.piece_type == PieceType::Knight)
+            })
+            .collect();
+        let ranks = if chess.current_turn() == Color::White {
+            2..=3
+        } else {
+            4..=5
+        };
+        let after = |from: Square, to: Square| {
+            let mut knights = knights.clone();
+            knights.retain(|knight| *knight != from);
+            knights.push(to);
+            knights.sort();
+            knights
+        };
+        let mov = chess
+            .legal_moves()
+            .into_iter()
+            .filter(|mov| knights.contains(&mov.from) && ranks.contains(&mov.to.rank()))
+            .min_by_key(|mov| {
+                let position = after(mov.from, mov.to);
+                seen.iter().filter(|seen| **seen == position).count()
+            })
+            .unwrap();
+        seen.push(after(mov.from, mov.to));
+        chess.play_move(mov).unwrap();
+    }
+}
+
+#[test]
+fn fifty_move_rule() {
+    let mut chess = init_standard_chess();
+    let mut seen = Vec::new();
+    wander_knights(&mut chess, 99, &mut seen);
+    assert_eq!(None, chess.claimable_draw());
+    wander_knights(&mut chess, 1, &mut seen);
+    assert_eq!(100, chess.halfmove_clock());
+    assert_eq!(GameStatus::Ongoing, chess.status());
+    assert_eq!(Some(DrawReason::FiftyMoveRule), chess.claimable_draw());
+}
+
+#[test]
+fn seventy_five_move_rule() {
+    let mut chess = init_standard_chess();
+    let mut seen = Vec::new();
+    wander_knights(&mut chess, 149, &mut seen);
+    assert_eq!(GameStatus::Ongoing, chess.status());
+    wander_knights(&mut chess, 1, &mut seen);
+    assert_eq!(
+        GameStatus::Draw {
+            reason: DrawReason::SeventyFiveMoveRule
+        },
+        chess.status()
+    );
+}
+
+fn promotion_race() -> ChessGame {
+    let mut chess = init_standard_chess();
+    for mov in &[
+        "h2 h4", "g7 g5", "h4 g5", "h7 h6", "g5 h6", "f8 g7", "h6 g7", "a7 a6",
+    ] {
+        chess.move_piece(mov.to_string()).unwrap();
+    }
+    chess
+}
+
+#[test]
+fn promotion_in_move() {
+    let mut chess = promotion_race();
+    chess.move_piece("g7 h8n".to_string()).unwrap();
+    assert_eq!(Color::Black, chess.current_turn());
+    assert_eq!(None, chess.pending_promotion());
+    assert_eq!(
+        Some(PieceType::Knight),
+        chess.get_board()[7][7]
+            .as_ref()
+            .map(|p| p.piece_type.clone())
+    );
+}
+
+#[test]
+fn pending_promotion() {
+    let mut chess = promotion_race();
+    chess.move_piece("g7 h8".to_string()).unwrap();
+    assert_eq!(Some("h8".parse().unwrap()), chess.pending_promotion());
+    assert_eq!(
+        Err(ChessError::PromotionPending),
+        chess.move_piece("a6 a5".to_string())
+    );
+    assert_eq!(
+        Err(ChessError::InvalidPromotion("h8".parse().unwrap())),
+        chess.promote(PieceType::King)
+    );
+    chess.promotion("h8R".to_string()).unwrap();
+    assert_eq!(Color::Black, chess.current_turn());
+    chess.move_piece("a6 a5".to_string()).unwrap();
+}
+
+#[test]
+fn default_promotion() {
+    let mut chess = promotion_race();
+    chess.set_default_promotion(Some(PieceType::Queen));
+    chess.move_piece("g7 h8".to_string()).unwrap();
+    assert_eq!(None, chess.pending_promotion());
+    assert_eq!(
+        Some(PieceType::Queen),
+        chess.get_board()[7][7]
+            .as_ref()
+            .map(|p| p.piece_type.clone())
+    );
+}
+
+#[test]
+fn stalemate() {
+    let mut chess = init_standard_chess();
+    // Sam Loyd's ten move stalemate
+    for mov in &[
+        "e2 e3", "a7 a5", "d1 h5", "a8 a6", "h5 a5", "h7 h5", "h2 h4", "a6 h6", "a5 c7", "f7 f6",
+        "c7 d7", "e8 f7", "d7 b7", "d8 d3", "b7 b8", "d3 h7", "b8 c8", "f7 g6", "c8 e6",
+    ] {
+        assert_eq!(GameStatus::Ongoing, chess.status());
+        chess.move_piece(mov.to_string()).unwrap();
+    }
+    assert_eq!(GameStatus::Stalemate, chess.status());
+    assert_eq!(None, chess.status().winner());
+}
+
+#[test]
+fn undo_and_redo() {
+    let mut chess = init_standard_chess();
+    assert_eq!(Err(ChessError::NothingToUndo), chess.undo());
+    let start = chess.get_board();
+    chess.move_piece("e2 e4".to_string()).unwrap();
+    chess.move_piece("d7 d5".to_string()).unwrap();
+    chess.move_piece("e4 e5".to_string()).unwrap();
+    chess.move_piece("f7 f5".to_string()).unwrap();
+    let after_f5 = chess.get_board();
+    assert_eq!(Err(ChessError::NothingToRedo), chess.redo());
+
+    chess.undo().unwrap();
+    assert_eq!(3, chess.ply());
+    assert_eq!(Color::Black, chess.current_turn());
+    chess.redo().unwrap();
+    assert_eq!(after_f5, chess.get_board());
+    // the en passant chance comes back with the position
+    chess.move_piece("e5 f6".to_string()).unwrap();
+    chess.undo().unwrap();
+    assert_eq!(after_f5, chess.get_board());
+
+    chess.goto_ply(0).unwrap();
+    assert_eq!(start, chess.get_board());
+    assert_eq!(Color::White, chess.current_turn());
+    assert_eq!(Err(ChessError::NoSuchPly(9)), chess.goto_ply(9));
+    chess.goto_ply(4).unwrap();
+    chess.move_piece("e5 f6".to_string()).unwrap();
+
+    // playing a different move drops the moves that were taken back
+    chess.goto_ply(2).unwrap();
+    chess.move_piece("b1 c3".to_string()).unwrap();
+    assert_eq!(Err(ChessError::NothingToRedo), chess.redo());
+}
+
+#[test]
+fn undo_restores_castling_and_outcome() {
+    let mut chess = init_standard_chess();
+    for mov in &[
+        "e2 e4", "e7 e5", "g1 f3", "b8 c6", "f1 c4", "g8 f6", "e1 g1",
+    ] {
+        chess.move_piece(mov.to_string()).unwrap();
+    }
+    chess.undo().unwrap();
+    chess.move_piece("h1 g1".to_string()).unwrap();
+    chess.undo().unwrap();
+    assert!(chess
+        .legal_moves()
+        .iter()
+        .any(|mov| mov.to == "g1".parse().unwrap() && mov.from == "e1".parse().unwrap()));
+
+    let mut chess = init_standard_chess();
+    for mov in &["f2 f3", "e7 e5", "g2 g4", "d8 h4"] {
+        chess.move_piece(mov.to_string()).unwrap();
+    }
+    assert!(chess.status().is_over());
+    chess.undo().unwrap();
+    assert_eq!(GameStatus::Ongoing, chess.status());
+    chess.move_piece("d8 e7".to_string()).unwrap();
+}
+
+#[test]
+fn undo_pending_promotion() {
+    let mut chess = promotion_race();
+    chess.move_piece("g7 h8".to_string()).unwrap();
+    chess.undo().unwrap();
+    assert_eq!(None, chess.pending_promotion());
+    assert_eq!(Color::White, chess.current_turn());
+    chess.move_piece("g7 h8r".to_string()).unwrap();
+}
+
+#[test]
+fn history_records() {
+    let mut chess = init_standard_chess();
+    for mov in &[
+        "e2 e4", "d7 d5", "e4 e5", "f7 f5", "e5 f6", "e8 f7", "g1 f3", "b8 c6", "f3 g5",
+    ] {
+        chess.move_piece(mov.to_string()).unwrap();
+    }
+    let san: Vec<&str> = chess
+        .history()
+        .iter()
+        .map(|record| record.san.as_str())
+        .collect();
+    assert_eq!(
+        vec!["e4", "d5", "e5", "f5", "exf6", "Kf7", "Nf3", "Nc6", "Ng5+"],
+        san
+    );
+    let passant = chess.history()[4];
+    assert_eq!(PieceType::Pawn, passant.piece);
+    assert_eq!(Some(PieceType::Pawn), passant.captured);
+    assert!(chess.history()[8].check && !chess.history()[8].checkmate);
+
+    chess.undo().unwrap();
+    assert_eq!(8, chess.history().len());
+    chess.move_piece("e1 g1".to_string()).unwrap_err();
+    chess.move_piece("f1 e2".to_string()).unwrap();
+    chess.move_piece("a7 a6".to_string()).unwrap();
+    chess.move_piece("e1 g1".to_string()).unwrap();
+    assert_eq!("O-O", chess.history().last().unwrap().san);
+
+    let mut chess = init_standard_chess();
+    for mov in &["f2 f3", "e7 e5", "g2 g4", "d8 h4"] {
+        chess.move_piece(mov.to_string()).unwrap();
+    }
+    let mate = chess.history()[3];
+    assert_eq!("Qh4#", mate.san);
+    assert!(mate.check && mate.checkmate);
+}
+
+#[test]
+fn fen_games() {
+    let mut chess = init_standard_chess();
+    for mov in &["e2 e4", "c7 c5", "g1 f3"] {
+        chess.move_piece(mov.to_string()).unwrap();
+    }
+    let fen = "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2";
+    assert_eq!(fen, chess.to_fen());
+
+    let mut loaded = ChessGame::from_fen(fen).unwrap();
+    assert_eq!(Color::Black, loaded.current_turn());
+    assert_eq!(1, loaded.halfmove_clock());
+    assert_eq!(2, loaded.fullmove_number());
+    loaded.move_piece("d7 d5".to_string()).unwrap();
+    assert_eq!(
+        "rnbqkbnr/pp2pppp/8/2pp4/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq d6 0 3",
+        loaded.to_fen()
+    );
+
+    let mated = ChessGame::from_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 40").unwrap();
+    assert_eq!(
+        GameStatus::Checkmate {
+            winner: Color::White
+        },
+        mated.status()
+    );
+    assert!(ChessGame::from_fen("not a position").is_err());
+}
+
+#[test]
+fn san_moves() {
+    let mut chess = init_standard_chess();
+    let mut play = |mov: &str| chess.move_piece(mov.to_string());
+    let played: Vec<String> = ["e4", "d5", "exd5", "Qxd5", "Nc3", "Qe5+"]
+        .iter()
+        .map(|mov| play(mov).unwrap())
+        .collect();
+    assert_eq!(vec!["e4", "d5", "exd5", "Qxd5", "Nc3", "Qe5+"], played);
+    assert_eq!(
+        Err(ChessError::AmbiguousMove("Ne2".to_string())),
+        play("Ne2")
+    );
+    assert_eq!(Ok("Nge2".to_string()), play("Nge2"));
+    assert_eq!(Ok("Bg4".to_string()), play("Bg4"));
+    // coordinates still work and answer in SAN too
+    assert_eq!(Ok("d4".to_string()), play("d2 d4"));
+    assert_eq!(Ok("Bxe2".to_string()), play("Bxe2"));
+    assert_eq!(
+        Err(ChessError::NoMatchingMove("O-O".to_string())),
+        play("O-O")
+    );
+}
+
+#[test]
+fn uci_moves() {
+    let mut chess = promotion_race();
+    assert_eq!(Ok("gxh8=N".to_string()), chess.play_uci("g7h8n"));
+    for mov in &["g8f6", "g1f3", "e7e6", "e2e3", "d8e7", "f1d3", "a6a5"] {
+        chess.move_piece(mov.to_string()).unwrap();
+    }
+    assert_eq!(Ok("O-O".to_string()), chess.play_uci("e1g1"));
+    assert_eq!(
+        vec![
+            "h2h4", "g7g5", "h4g5", "h7h6", "g5h6", "f8g7", "h6g7", "a7a6", "g7h8n", "g8f6",
+            "g1f3", "e7e6", "e2e3", "d8e7", "f1d3", "a6a5", "e1g1"
+        ],
+        chess.uci_moves()
+    );
+    assert_eq!(
+        Err(ChessError::BadNotation("z9z8".to_string())),
+        chess.play_uci("z9z8")
+    );
+}
+
+#[test]
+fn chess960_games() {
+    assert_eq!(
+        "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1",
+        init_chess960(0).unwrap().to_fen()
+    );
+    assert!(matches!(
+        init_chess960(960),
+        Err(ChessError::InvalidPosition(_))
+    ));
+
+    let mut chess = init_chess960(518).unwrap();
+    assert_eq!(init_standard_chess().to_fen(), chess.to_fen());
+    for mov in &["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5"] {
+        chess.move_piece(mov.to_string()).unwrap();
+    }
+    assert_eq!(Ok("O-O".to_string()), chess.play_uci("e1h1"));
+    assert_eq!(Some(&"e1h1".to_string()), chess.uci_moves().last());
+    assert_eq!(
+        "r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 5 4",
+        chess.to_fen()
+    );
+}
+
+#[test]
+fn sized_boards() {
+    let mut chess = ChessGame::from_fen("rnqknr/pppppp/6/6/PPPPPP/RNQKNR w - - 0 1").unwrap();
+    assert_eq!(Dimensions::new(6, 6).unwrap(), chess.dimensions());
+    assert_eq!(16, chess.legal_moves().len());
+    assert_eq!(Ok("Nc3".to_string()), chess.move_piece("Nc3".to_string()));
+    assert_eq!(
+        Err(ChessError::OutOfBounds((6, 0))),
+        chess.pick_square("g1".parse().unwrap())
+    );
+    assert_eq!(
+        "rnqknr/pppppp/6/6/PPPPPP/RNQKNR w - - 0 1",
+        chess.start_fen()
+    );
+
+    // castling on a 10x8 board brings the king to the i- or c-file
+    let mut chess = ChessGame::from_fen("r4k3r/10/10/10/10/10/10/R4K3R w KQkq - 0 1").unwrap();
+    assert_eq!(Ok("O-O".to_string()), chess.move_piece("O-O".to_string()));
+    assert_eq!(
+        Ok("O-O-O".to_string()),
+        chess.move_piece("f8c8".to_string())
+    );
+    assert_eq!("2kr5r/10/10/10/10/10/10/R6RK1 w - - 2 2", chess.to_fen());
+
+    let mut chess = ChessGame::from_fen("4k5/P9/10/10/10/10/10/10/10/4K5 w - - 0 1").unwrap();
+    assert_eq!(
+        Err(ChessError::BadNotation("e11".to_string())),
+        chess.move_piece("e11".to_string())
+    );
+    assert_eq!(
+        Ok("a10=Q+".to_string()),
+        chess.move_piece("a10=Q".to_string())
+    );
+    assert_eq!(Some(&"a9a10q".to_string()), chess.uci_moves().last());
+    assert_eq!("Q3k5/10/10/10/10/10/10/10/10/4K5 b - - 0 1", chess.to_fen());
+
+    // a double step would land on the last rank of a board of four ranks
+    let mut chess = ChessGame::from_fen("3k/4/P3/1K2 w - - 0 1").unwrap();
+    assert_eq!(vec!["a3"], chess.pick_piece("a2".to_string()).unwrap());
+    assert!(chess.play_uci("a2a4q").is_err());
+}
+
+#[test]
+fn custom_pieces() {
+    let capablanca = "rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1";
+    assert!(ChessGame::from_fen(capablanca).is_err());
+    let pieces = [PieceDefinition::archbishop(), PieceDefinition::chancellor()];
+    let mut chess = ChessGame::from_fen_with_pieces(capablanca, &pieces).unwrap();
+    assert_eq!(28, chess.legal_moves().len());
+    for san in ["d4", "d5", "Af4", "Ae6", "Ci3", "Cg6", "Axc7"] {
+        assert_eq!(Ok(san.to_string()), chess.move_piece(san.to_string()));
+    }
+    assert_eq!(
+        "rn1bqkb1nr/ppA1pppppp/4a1c3/3p6/3P6/8C1/PPP1PPPPPP/RN1BQKB1NR b KQkq - 0 4",
+        chess.to_fen()
+    );
+}
+
+#[test]
+fn three_check() {
+    let mut chess = ChessGame::new(ThreeCheck).unwrap();
+    assert_eq!(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3+3 0 1",
+        chess.to_fen()
+    );
+    for san in ["e4", "e5", "Bc4", "Nc6", "Bxf7+", "Kxf7", "Qh5+", "Ke7"] {
+        assert_eq!(Ok(san.to_string()), chess.move_piece(san.to_string()));
+    }
+    assert_eq!(2, chess.checks_given(Color::White));
+    assert_eq!(
+        "r1bq1bnr/ppppk1pp/2n5/4p2Q/4P3/8/PPPP1PPP/RNB1K1NR w KQ - 1+3 2 5",
+        chess.to_fen()
+    );
+    assert_eq!(
+        Ok("Game is over! White wins by three checks!".to_string()),
+        chess.move_piece("Qxe5+".to_string())
+    );
+    assert_eq!(Some(Color::White), chess.status().winner());
+
+    chess.undo().unwrap();
+    assert_eq!(GameStatus::Ongoing, chess.status());
+    let resumed = ChessGame::from_variant_fen(ThreeCheck, &chess.to_fen()).unwrap();
+    assert_eq!(2, resumed.checks_given(Color::White));
+    assert_eq!(chess.to_fen(), resumed.start_fen());
+}
@@ -0,0 +1,99 @@
+use maltebl_chess::board_logic::parse_fen;
+
+fn perft(fen: &str, depth: usize) -> u64 {
+    let (board, to_move, ..) = parse_fen(fen).unwrap();
+    board.perft(to_move, depth)
+}
+
+#[test]
+fn start_position() {
+    let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    assert_eq!(
+        vec![20, 400, 8902, 197281],
+        (1..=4).map(|depth| perft(fen, depth)).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn kiwipete() {
+    let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    assert_eq!(
+        vec![48, 2039, 97862],
+        (1..=3).map(|depth| perft(fen, depth)).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn position_3() {
+    let fen = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+    assert_eq!(
+        vec![14, 191, 2812, 43238, 674624],
+        (1..=5).map(|depth| perft(fen, depth)).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn position_4() {
+    let fen = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+    assert_eq!(
+        vec![6, 264, 9467, 422333],
+        (1..=4).map(|depth| perft(fen, depth)).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn position_5() {
+    let fen = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+    assert_eq!(
+        vec![44, 1486, 62379],
+        (1..=3).map(|depth| perft(fen, depth)).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn position_6() {
+    let fen = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+    assert_eq!(
+        vec![46, 2079, 89890],
+        (1..=3).map(|depth| perft(fen, depth)).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn divide() {
+    let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    let (board, to_move, ..) = parse_fen(fen).unwrap();
+    let divided = board.divide(to_move, 3);
+    assert_eq!(20, divided.len());
+    assert_eq!(8902, divided.iter().map(|(_, nodes)| nodes).sum::<u64>());
+    let e4 = divided
+        .iter()
+        .find(|(mov, _)| mov.to_uci() == "e2e4")
+        .unwrap();
+    assert_eq!(600, e4.1);
+}
+
+#[test]
+fn chess960_positions() {
+    for (fen, counts) in &[
+        (
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+            vec![21, 528, 12189, 326672],
+        ),
+        (
+            "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",
+            vec![21, 807, 18002, 667366],
+        ),
+        (
+            "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",
+            vec![20, 479, 10471, 273318],
+        ),
+    ] {
+        assert_eq!(
+            *counts,
+            (1..=4).map(|depth| perft(fen, depth)).collect::<Vec<_>>(),
+            "{}",
+            fen
+        );
+    }
+}
@@ -8,8 +8,9 @@ use std::sync::Arc;
 
 pub mod action {
     use druid::Selector;
+    use maltebl_chess::Move;
 
-    pub const MAKE_MOVE: Selector<String> = Selector::new("make_move");
+    pub const MAKE_MOVE: Selector<Move> = Selector::new("make_move");
 }
 
 pub struct Delegate {
@@ -26,9 +27,9 @@ impl AppDelegate<AppState> for Delegate {
         _env: &Env,
     ) -> bool {
         if let Some(command) = cmd.get(action::MAKE_MOVE) {
-            let msg = self.game.move_piece(command.to_owned());
+            let msg = self.game.play_move(command.clone());
             data.message = Some(match msg {
-                Err(txt) => txt.to_owned(),
+                Err(err) => err.to_string(),
                 _ => "".to_owned(),
             });
             data.board = Arc::new(self.game.get_board());
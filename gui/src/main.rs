@@ -9,15 +9,18 @@ use crate::state::AppState;
 use crate::ui::main_ui;
 
 use maltebl_chess::chess_game::*;
+use maltebl_chess::piece_logic::PieceType;
 
 use druid::{AppLauncher, Env, PlatformError, WindowDesc};
 
 fn main() -> Result<(), PlatformError> {
-    let game = init_standard_chess();
+    let mut game = init_standard_chess();
+    game.set_default_promotion(Some(PieceType::Queen));
     let data = AppState::new(game.get_board());
+    let dims = game.dimensions();
     let delegate = Delegate { game };
 
-    let window = WindowDesc::new(main_ui)
+    let window = WindowDesc::new(move || main_ui(dims))
         .title(|data: &AppState, _env: &Env| {
             format!(
                 "Chess {}",
@@ -28,7 +31,7 @@ fn main() -> Result<(), PlatformError> {
             )
         })
         .resizable(false)
-        .window_size((400.0, 400.0));
+        .window_size((50.0 * dims.files() as f64, 50.0 * dims.ranks() as f64));
 
     let app = AppLauncher::with_window(window);
     app.delegate(delegate).launch(data)?;
@@ -1,13 +1,29 @@
+use std::convert::TryFrom;
 use std::sync::Arc;
 
 use maltebl_chess::board_logic::Board;
 use maltebl_chess::piece_logic::Piece;
+use maltebl_chess::{ChessError, Square};
 
 use druid::Data;
 
 #[derive(Data, Copy, Clone, Debug, PartialEq)]
 pub struct Position(pub i32, pub i32);
 
+impl From<Square> for Position {
+    fn from(square: Square) -> Self {
+        Position(square.file() as i32, square.rank() as i32)
+    }
+}
+
+impl TryFrom<Position> for Square {
+    type Error = ChessError;
+
+    fn try_from(position: Position) -> Result<Self, Self::Error> {
+        Square::try_from((position.0 as usize, position.1 as usize))
+    }
+}
+
 #[derive(Data, Clone)]
 pub struct AppState {
     pub board: Arc<Board>,
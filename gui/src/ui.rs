@@ -5,27 +5,29 @@ use crate::tile::Tile;
 
 use maltebl_chess::{
     piece_logic::{self, Piece, PieceType},
-    to_notation,
+    ChessError, Dimensions, Move, Square,
 };
 
-use druid::{widget::*, WidgetExt, *};
+use std::convert::TryFrom;
 
-const NUM_ROWS: i32 = 8;
-const NUM_COLS: i32 = 8;
+use druid::{widget::*, WidgetExt, *};
 
-pub fn main_ui() -> impl Widget<AppState> {
-    make_board()
+pub fn main_ui(dims: Dimensions) -> impl Widget<AppState> {
+    make_board(dims)
 }
 
-fn make_board() -> impl Widget<AppState> {
+fn make_board(dims: Dimensions) -> impl Widget<AppState> {
+    let num_rows = dims.ranks() as i32;
+    let num_cols = dims.files() as i32;
+
     let make_row = |y: i32| {
-        (0..NUM_COLS).fold(Flex::row(), |col, x| {
+        (0..num_cols).fold(Flex::row(), |col, x| {
             col.with_flex_child(make_tile(Position(x, y)), 1.0)
         })
     };
 
     let make_rows = || {
-        (0..NUM_ROWS).fold(Flex::column(), |row, y| {
+        (0..num_rows).fold(Flex::column(), |row, y| {
             row.with_flex_child(make_row(y), 1.0)
         })
     };
@@ -72,11 +74,8 @@ fn make_tile(pos: Position) -> impl Widget<AppState> {
                     return;
                 }
 
-                let cpos = (prev.0 as usize, prev.1 as usize);
-                let tpos = (pos.0 as usize, pos.1 as usize);
-
-                let mut doit = || -> Result<String, String> {
-                    let command = format!("{} {}", to_notation(cpos)?, to_notation(tpos)?);
+                let mut doit = || -> Result<String, ChessError> {
+                    let command = Move::new(Square::try_from(prev)?, Square::try_from(pos)?);
                     ctx.submit_command(Command::new(action::MAKE_MOVE, command), None);
                     Ok("".to_owned())
                 };
@@ -126,10 +125,18 @@ impl<T: Data> Tile2<T> {
                 piece_logic::Color::Black => ColorUtil::hsl(0.1, 0.3, 0.1),
             };
 
-            let color = format!("{:?}", color);
-            let color = &color[..color.len() - 2];
-            let svg_data = piece_svg_colored(&t.0, color).parse::<SvgData>();
-            Svg::new(svg_data.unwrap()).fix_width(30.0).center()
+            let color_text = format!("{:?}", color);
+            let color_text = &color_text[..color_text.len() - 2];
+            match piece_svg_colored(&t.0, color_text) {
+                Some(svg) => Svg::new(svg.parse::<SvgData>().unwrap())
+                    .fix_width(30.0)
+                    .center(),
+                // custom pieces without an image show their letter
+                None => Label::new(t.0.letter().to_string())
+                    .with_text_color(color)
+                    .with_text_size(28.0)
+                    .center(),
+            }
         });
         true
     }
@@ -175,35 +182,29 @@ impl<T: Data> Widget<T> for Tile2<T> {
 }
 
 #[allow(dead_code)]
-fn piece_char(piece: &Piece) -> &str {
-    match piece.piece_type {
-        PieceType::King => "K",
-        PieceType::Queen => "Q",
-        PieceType::Rook => "R",
-        PieceType::Knight => "N",
-        PieceType::Bishop => "B",
-        PieceType::Pawn => "P",
-    }
+fn piece_char(piece: &Piece) -> String {
+    piece.piece_type.letter().to_string()
 }
 
-fn piece_svg_raw(piece: &PieceType) -> &str {
-    match piece {
+fn piece_svg_raw(piece: &PieceType) -> Option<&str> {
+    Some(match piece {
         PieceType::King => include_str!("../assets/k.svg"),
         PieceType::Queen => include_str!("../assets/q.svg"),
         PieceType::Rook => include_str!("../assets/r.svg"),
         PieceType::Knight => include_str!("../assets/n.svg"),
         PieceType::Bishop => include_str!("../assets/b.svg"),
         PieceType::Pawn => include_str!("../assets/p.svg"),
-    }
+        PieceType::Custom(definition) => return definition.svg(),
+    })
 }
 
-fn piece_svg_colored(piece: &PieceType, color: &str) -> String {
-    let raw = piece_svg_raw(piece);
+fn piece_svg_colored(piece: &PieceType, color: &str) -> Option<String> {
+    let raw = piece_svg_raw(piece)?;
     let old_start_end = raw.find('>').expect("faulty svg?");
 
     let margin = 0;
     let org_size = (24, 24);
     let new_start = format!("<svg fill=\"{}\" xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" version=\"1.1\" viewBox=\"{} {} {} {}\" xml:space=\"preserve\"", color, 0, 0, org_size.0+margin, org_size.1+margin);
 
-    format!("{}{}", new_start, &raw[old_start_end..])
+    Some(format!("{}{}", new_start, &raw[old_start_end..]))
 }